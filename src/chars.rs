@@ -31,3 +31,63 @@ pub(crate) fn is_email_local_char(c: char) -> bool {
         _ => c >= '\u{80}' && !c.is_whitespace(),
     }
 }
+
+/// Check if a character is a symbol such as an emoji, an arrow or a dingbat.
+///
+/// This approximates the Unicode general categories "Symbol, other" (So) and "Symbol, modifier"
+/// (Sk) for non-ASCII characters, plus the joiners and modifiers used in emoji sequences.
+pub(crate) fn is_symbol(c: char) -> bool {
+    match c {
+        '\u{A6}' | '\u{A8}' | '\u{A9}' | '\u{AE}' | '\u{AF}' | '\u{B0}' | '\u{B4}' | '\u{B8}' => {
+            true
+        }
+        // Modifier letters and spacing modifiers
+        '\u{2C2}'..='\u{2C5}' | '\u{2D2}'..='\u{2DF}' => true,
+        // Zero width joiner and variation selectors, used in emoji sequences
+        '\u{200D}' | '\u{FE00}'..='\u{FE0F}' => true,
+        // Letterlike symbols, arrows, mathematical operators, technical symbols, enclosed
+        // alphanumerics, box drawing, geometric shapes, miscellaneous symbols and dingbats
+        '\u{2100}'..='\u{214F}' | '\u{2190}'..='\u{2BFF}' => true,
+        // CJK symbols
+        '\u{3200}'..='\u{33FF}' => true,
+        // Mahjong and domino tiles, playing cards, enclosed alphanumerics and ideographs,
+        // emoji, pictographs and other symbols (including skin tone modifiers)
+        '\u{1F000}'..='\u{1FAFF}' => true,
+        // Tags used in flag emoji
+        '\u{E0020}'..='\u{E007F}' => true,
+        _ => false,
+    }
+}
+
+/// Check if a character is an invisible zero width character: ZERO WIDTH SPACE, ZERO WIDTH
+/// NON-JOINER, ZERO WIDTH JOINER or ZERO WIDTH NO-BREAK SPACE (BOM).
+pub(crate) fn is_zero_width(c: char) -> bool {
+    matches!(c, '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{FEFF}')
+}
+
+/// Check if a character is a bidirectional formatting character: ARABIC LETTER MARK,
+/// LEFT-TO-RIGHT MARK, RIGHT-TO-LEFT MARK, the embeddings and overrides (U+202A to U+202E) and
+/// the isolates (U+2066 to U+2069).
+pub(crate) fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{61C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Check if a character is in one of the ranges used for emoji, e.g. emoticons, pictographs,
+/// miscellaneous symbols, dingbats and regional indicators (flags).
+pub(crate) fn is_emoji(c: char) -> bool {
+    matches!(c,
+        '\u{2600}'..='\u{27BF}'
+        | '\u{2B00}'..='\u{2BFF}'
+        | '\u{FE0F}'
+        | '\u{1F000}'..='\u{1FAFF}'
+        | '\u{E0020}'..='\u{E007F}')
+}
+
+/// The ASCII character for a fullwidth form (U+FF01 to U+FF5E), e.g. `h` for `ｈ` and `/` for
+/// `／`. This is what NFKC normalization does for these characters.
+pub(crate) fn fullwidth_to_ascii(c: char) -> Option<char> {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => std::char::from_u32(c as u32 - 0xFEE0),
+        _ => None,
+    }
+}
@@ -26,13 +26,26 @@
 //! ```
 
 use std::char;
+use std::net::Ipv4Addr;
 
+use crate::percent::decode_hex_pair;
+use crate::url::WhitespacePolicy;
+
+/// Find the end of the authority at the start of `s`, and the index of the last dot of the host.
+///
+/// With `kept_sub_delims` (see `SchemeSet::keep_sub_delims`), the listed sub-delims are part of
+/// the authority and can be at its end, and the other sub-delims end it. Non-ASCII whitespace
+/// ends the authority according to `whitespace`.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn find_authority_end(
     s: &str,
     mut userinfo_allowed: bool,
     require_host: bool,
     port_allowed: bool,
     iri_parsing_enabled: bool,
+    allow_short_ipv4: bool,
+    kept_sub_delims: Option<&[char]>,
+    whitespace: &WhitespacePolicy,
 ) -> (Option<usize>, Option<usize>) {
     // Handle IPv6 literals: IP-literal = "[" ( IPv6address / IPvFuture ) "]"
     // Per RFC 2732 and RFC 3986
@@ -72,8 +85,9 @@ pub(crate) fn find_authority_end(
                 if !iri_parsing_enabled {
                     break;
                 }
-                // Exclude Unicode whitespace (e.g., NBSP, EM SPACE, IDEOGRAPHIC SPACE)
-                if c.is_whitespace() {
+                // Exclude Unicode whitespace (e.g., NBSP, EM SPACE, IDEOGRAPHIC SPACE), depending
+                // on the policy
+                if whitespace.terminates(c) {
                     break;
                 }
                 // Can start or end a domain label, but not numeric
@@ -134,7 +148,29 @@ pub(crate) fn find_authority_end(
 
                 false
             }
+            // pct-encoded, e.g. an `@` in a password like `p%40ss`
+            '%' if userinfo_allowed
+                && decode_hex_pair(&s.as_bytes()[i + 1..]).is_some()
+                && continues_userinfo(&s[i..]) =>
+            {
+                // Hostnames can't contain these, but the userinfo can
+                maybe_host = false;
+
+                false
+            }
             // sub-delims
+            '!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '=' if matches!(kept_sub_delims, Some(kept) if kept.contains(&c)) =>
+            {
+                // Can't be in hostnames, but part of the authority for this scheme
+                maybe_host = false;
+
+                true
+            }
+            '!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '='
+                if kept_sub_delims.is_some() =>
+            {
+                break;
+            }
             '!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '=' => {
                 // Can't be in hostnames, but we treat them as delimiters
                 host_ended = true;
@@ -167,6 +203,12 @@ pub(crate) fn find_authority_end(
                 // opinions about all the host bits.
                 userinfo_allowed = false;
 
+                if s[i + 1..].starts_with('[') {
+                    // The host is an IPv6 literal, e.g. in `ssh://git@[::1]/repo.git`
+                    let (end, _) = find_ipv6_authority_end(&s[i + 1..], port_allowed);
+                    return (end.map(|end| i + 1 + end), None);
+                }
+
                 maybe_last_dot = None;
                 last_dot = None;
                 dot_allowed = false;
@@ -198,11 +240,21 @@ pub(crate) fn find_authority_end(
         }
     }
 
+    if let Some(end) = end {
+        if !valid_port(&s[..end]) {
+            return (None, None);
+        }
+    }
+
     if require_host {
         if maybe_host {
             if all_numeric {
-                // For IPv4 addresses, require 4 numbers
-                if number_dots != 3 {
+                // For IPv4 addresses, require 4 numbers. Something like `1.2.3` is not treated
+                // as an IPv4 address, and also not as a domain (`3` is not a valid TLD), so it's
+                // rejected. Valid IPv4 addresses don't need a TLD check.
+                // Shorthand addresses like `10.0.1` can be allowed explicitly.
+                let short_ipv4 = allow_short_ipv4 && (number_dots == 1 || number_dots == 2);
+                if number_dots != 3 && !short_ipv4 {
                     return (None, None);
                 }
             } else {
@@ -210,7 +262,7 @@ pub(crate) fn find_authority_end(
                 // check that the TLD looks reasonable. This is to avoid linking things like
                 // `abc@v1.1`.
                 if let Some(last_dot) = last_dot {
-                    if !valid_tld(&s[last_dot + 1..]) {
+                    if !valid_tld(&s[last_dot + 1..], false) {
                         return (None, None);
                     }
                 }
@@ -225,12 +277,131 @@ pub(crate) fn find_authority_end(
     }
 }
 
-fn valid_tld(tld: &str) -> bool {
-    tld.chars()
-        .take_while(|c| c.is_ascii_alphabetic())
-        .take(2)
-        .count()
-        >= 2
+/// Check whether `s` continues the userinfo of an authority up to its `@`, e.g. `%40ss@host`
+/// for the password in `user:p%40ss@host`. Otherwise a `%` ends the host.
+fn continues_userinfo(s: &str) -> bool {
+    for c in s.chars() {
+        match c {
+            '@' => return true,
+            // unreserved, pct-encoded, sub-delims and `:`
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' | '_' | '~' | '%' | ':' => {}
+            '!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '=' => {}
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Check whether `s` is an IPv4 address in dotted-decimal form with 4 parts in range, e.g.
+/// `192.168.0.1` but not `1.2.3` or `1.2.3.400`.
+pub(crate) fn is_ipv4(s: &str) -> bool {
+    let mut parts = 0;
+    for part in s.split('.') {
+        parts += 1;
+        if part.is_empty()
+            || part.len() > 3
+            || !part.bytes().all(|b| b.is_ascii_digit())
+            || part.parse::<u8>().is_err()
+        {
+            return false;
+        }
+    }
+    parts == 4
+}
+
+/// Parse an IPv4 address in any of the forms that browsers accept, e.g. `127.0.0.1`, `127.1`,
+/// `2130706433`, `0x7f.0.0.1` or `0177.0.0.1` (see the IPv4 parser of the WHATWG URL standard).
+///
+/// There can be 1 to 4 parts. Parts starting with `0x` are hexadecimal, other parts starting
+/// with `0` are octal. The last part fills the remaining bytes of the address, the others must
+/// be at most 255.
+pub(crate) fn parse_numeric_ipv4(s: &str) -> Option<Ipv4Addr> {
+    let parts = s.split('.').collect::<Vec<_>>();
+    if parts.len() > 4 {
+        return None;
+    }
+    let mut address: u64 = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let value = parse_ipv4_number(part)?;
+        if i == parts.len() - 1 {
+            let remaining_bits = 8 * (5 - parts.len() as u32);
+            if value >= 1 << remaining_bits {
+                return None;
+            }
+            address = (address << remaining_bits) | value;
+        } else {
+            if value > 255 {
+                return None;
+            }
+            address = (address << 8) | value;
+        }
+    }
+    Some(Ipv4Addr::from(address as u32))
+}
+
+/// Parse a part of an IPv4 address in decimal, octal (with a leading `0`) or hexadecimal (with
+/// a leading `0x`).
+fn parse_ipv4_number(s: &str) -> Option<u64> {
+    let (digits, radix) = if s.starts_with("0x") || s.starts_with("0X") {
+        (&s[2..], 16)
+    } else if s.len() > 1 && s.starts_with('0') {
+        (&s[1..], 8)
+    } else {
+        (s, 10)
+    };
+    if digits.is_empty() {
+        // `0x` on its own is 0, but empty parts are not allowed
+        return if radix == 16 { Some(0) } else { None };
+    }
+    if !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    // Numbers that don't fit are too large for an address anyway
+    u64::from_str_radix(digits, radix)
+        .ok()
+        .filter(|&value| value <= u64::from(u32::MAX))
+}
+
+/// Check that the port of the authority (if any) is in range, e.g. reject `example.org:99999`.
+fn valid_port(authority: &str) -> bool {
+    let host_and_port = match authority.rfind('@') {
+        Some(at) => &authority[at + 1..],
+        None => authority,
+    };
+    match host_and_port.rfind(':') {
+        Some(colon) => {
+            let port = &host_and_port[colon + 1..];
+            if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) {
+                port.parse::<u16>().is_ok()
+            } else {
+                true
+            }
+        }
+        None => true,
+    }
+}
+
+/// Check if a top-level domain looks valid: it starts with at least two letters, e.g. `com` or
+/// `co2` but not `v1`. With `strict`, it can only contain letters (including non-ASCII ones), or
+/// be the `xn--` form of an IDN.
+pub(crate) fn valid_tld(tld: &str, strict: bool) -> bool {
+    if !strict {
+        return tld
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .take(2)
+            .count()
+            >= 2;
+    }
+    match tld.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("xn--") => {
+            tld.len() > 4
+                && tld[4..]
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        }
+        _ => tld.chars().count() >= 2 && tld.chars().all(char::is_alphabetic),
+    }
 }
 
 /// Handle IPv6 literal addresses in the authority component.
@@ -290,6 +461,10 @@ fn find_ipv6_authority_end(s: &str, port_allowed: bool) -> (Option<usize>, Optio
             }
 
             if port_end > port_start {
+                if s[port_start..port_end].parse::<u16>().is_err() {
+                    // Port out of range
+                    return (None, None);
+                }
                 // Valid port found
                 (Some(port_end), None)
             } else {
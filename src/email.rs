@@ -1,15 +1,21 @@
+use std::net::Ipv6Addr;
 use std::ops::Range;
 
 use crate::chars::is_email_local_char;
-use crate::domains::find_authority_end;
+use crate::domains::{find_authority_end, is_ipv4, valid_tld};
 use crate::scanner::Scanner;
+use crate::url::WhitespacePolicy;
 
 /// Scan for email address starting from the trigger character "@".
 ///
-/// Based on RFC 6531, but also accepts invalid IDNs. Doesn't try to handle IP addresses in domain part or
-/// quoting in local part.
+/// Based on RFC 6531, but also accepts invalid IDNs. IP addresses in the domain part are only
+/// accepted in brackets (if enabled). Doesn't try to handle quoting in local part.
+#[derive(Clone)]
 pub struct EmailScanner {
     pub domain_must_have_dot: bool,
+    pub conservative_local: bool,
+    pub require_fqdn: bool,
+    pub allow_ip_domain: bool,
 }
 
 impl Scanner for EmailScanner {
@@ -34,7 +40,7 @@ impl EmailScanner {
         let mut first = None;
         let mut atom_boundary = true;
         for (i, c) in s.char_indices().rev() {
-            if Self::local_atom_allowed(c) {
+            if self.local_atom_allowed(c) {
                 first = Some(i);
                 atom_boundary = false;
             } else if c == '.' {
@@ -54,8 +60,33 @@ impl EmailScanner {
 
     // See "Domain" in RFC 5321, plus extension of "sub-domain" in RFC 6531
     fn find_end(&self, s: &str) -> Option<usize> {
-        if let (Some(end), last_dot) = find_authority_end(s, false, true, false, true) {
-            if !self.domain_must_have_dot || last_dot.is_some() {
+        if s.starts_with('[') {
+            return if self.allow_ip_domain {
+                find_address_literal_end(s)
+            } else {
+                None
+            };
+        }
+
+        if let (Some(end), last_dot) = find_authority_end(
+            s,
+            false,
+            true,
+            false,
+            true,
+            false,
+            None,
+            &WhitespacePolicy::All,
+        ) {
+            if is_ipv4(&s[..end]) {
+                // An IP address must be in brackets, see "address-literal" in RFC 5321
+                None
+            } else if self.require_fqdn {
+                match last_dot {
+                    Some(dot) if valid_tld(&s[dot + 1..end], true) => Some(end),
+                    _ => None,
+                }
+            } else if !self.domain_must_have_dot || last_dot.is_some() {
                 Some(end)
             } else {
                 None
@@ -66,7 +97,30 @@ impl EmailScanner {
     }
 
     // See "Atom" in RFC 5321, "atext" in RFC 5322
-    fn local_atom_allowed(c: char) -> bool {
-        is_email_local_char(c)
+    fn local_atom_allowed(&self, c: char) -> bool {
+        if self.conservative_local {
+            // The subset that is commonly used in practice (`.` is handled separately)
+            matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '%' | '+' | '-')
+        } else {
+            is_email_local_char(c)
+        }
+    }
+}
+
+/// Find the end of an address literal like `[192.168.0.1]` or `[IPv6:2001:db8::1]`, see
+/// "address-literal" in RFC 5321.
+fn find_address_literal_end(s: &str) -> Option<usize> {
+    let close = s.find(']')?;
+    let address = &s[1..close];
+    let valid = match address.get(..5) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("IPv6:") => {
+            address[5..].parse::<Ipv6Addr>().is_ok()
+        }
+        _ => is_ipv4(address),
+    };
+    if valid {
+        Some(close + 1)
+    } else {
+        None
     }
 }
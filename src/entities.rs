@@ -0,0 +1,69 @@
+//! Decoding of the HTML character references that are common in escaped URLs.
+
+use std::borrow::Cow;
+use std::char;
+
+/// Parse the character reference at the start of `s`, e.g. `&amp;`, `&#38;` or `&#x26;`.
+///
+/// Returns the character and the length of the reference. Only the named references for the
+/// characters that need escaping in HTML are supported (`&amp;`, `&lt;`, `&gt;`, `&quot;` and
+/// `&apos;`), plus decimal and hexadecimal numeric references. The `;` is required.
+pub(crate) fn parse_entity(s: &str) -> Option<(char, usize)> {
+    let rest = s.strip_prefix('&')?;
+    // The longest supported reference is `&#x10FFFF;`
+    let semicolon = rest.bytes().take(9).position(|b| b == b';')?;
+    let name = &rest[..semicolon];
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        _ => {
+            let number = name.strip_prefix('#')?;
+            let (digits, radix) = match number
+                .strip_prefix('x')
+                .or_else(|| number.strip_prefix('X'))
+            {
+                Some(hex) => (hex, 16),
+                None => (number, 10),
+            };
+            if digits.is_empty() || digits.len() > 8 || !digits.chars().all(|c| c.is_digit(radix)) {
+                return None;
+            }
+            char::from_u32(u32::from_str_radix(digits, radix).ok()?)?
+        }
+    };
+    Some((c, "&".len() + semicolon + ";".len()))
+}
+
+/// Decode the character references in `s` (see `parse_entity`). Other `&` are kept as they are.
+pub(crate) fn decode_entities(s: &str) -> Cow<'_, str> {
+    if !s.contains('&') {
+        return Cow::Borrowed(s);
+    }
+
+    let mut decoded = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        match parse_entity(rest) {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    if decoded == s {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(decoded)
+    }
+}
@@ -0,0 +1,44 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::Utf8Error;
+
+/// An error while finding links in a stream of input, e.g. from a reader.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ScanError {
+    /// Reading the input failed.
+    Io(io::Error),
+    /// The input is not valid UTF-8.
+    Utf8(Utf8Error),
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::Io(e) => write!(f, "failed to read input: {}", e),
+            ScanError::Utf8(e) => write!(f, "input is not valid UTF-8: {}", e),
+        }
+    }
+}
+
+impl Error for ScanError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ScanError::Io(e) => Some(e),
+            ScanError::Utf8(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ScanError {
+    fn from(e: io::Error) -> Self {
+        ScanError::Io(e)
+    }
+}
+
+impl From<Utf8Error> for ScanError {
+    fn from(e: Utf8Error) -> Self {
+        ScanError::Utf8(e)
+    }
+}
@@ -0,0 +1,98 @@
+//! Minimal HTML tag scanning, for skipping links that are already in an anchor.
+
+use std::ops::Range;
+
+use memchr::memchr;
+
+/// How far an anchor that is not closed continues, in bytes from the start of its `<a ...>` tag.
+/// Beyond this, the text is treated as not in an anchor, so that a malformed anchor doesn't hide
+/// all the links after it.
+const MAX_UNCLOSED_ANCHOR_LEN: usize = 2048;
+
+/// Find the ranges of `s` that are in anchors, from the start of `<a ...>` to the end of the
+/// matching `</a>`.
+///
+/// This is not a full HTML parser. It only looks at `a` tags and handles attribute values in
+/// quotes (which can contain `>`). Nested anchors end at the outermost `</a>`, closing tags
+/// without an open anchor are ignored, and an anchor that is not closed (or a start tag that is
+/// not finished) continues for at most `MAX_UNCLOSED_ANCHOR_LEN` bytes. The ranges are in order
+/// and don't overlap.
+pub(crate) fn find_anchors(s: &str) -> Vec<Range<usize>> {
+    let bytes = s.as_bytes();
+    let mut anchors = Vec::new();
+    let mut depth = 0;
+    let mut anchor_start = 0;
+    let mut i = 0;
+
+    while let Some(lt) = memchr(b'<', &bytes[i..]) {
+        let tag_start = i + lt;
+        let closing = bytes.get(tag_start + 1) == Some(&b'/');
+        let name_start = if closing {
+            tag_start + 2
+        } else {
+            tag_start + 1
+        };
+        if !is_anchor_name(&bytes[name_start.min(bytes.len())..]) {
+            // Some other tag, or just a `<` in text
+            i = tag_start + 1;
+            continue;
+        }
+
+        let tag_end = find_tag_end(bytes, name_start + 1);
+        if closing {
+            if depth > 0 {
+                depth -= 1;
+                if depth == 0 {
+                    anchors.push(anchor_start..tag_end);
+                }
+            }
+        } else {
+            if depth == 0 {
+                anchor_start = tag_start;
+            }
+            depth += 1;
+        }
+        i = tag_end;
+    }
+
+    if depth > 0 {
+        let mut end = s.len().min(anchor_start + MAX_UNCLOSED_ANCHOR_LEN);
+        while !s.is_char_boundary(end) {
+            end += 1;
+        }
+        anchors.push(anchor_start..end);
+    }
+    anchors
+}
+
+/// Check if `s` starts with the tag name `a`, e.g. `a href="...">` or `A>`.
+fn is_anchor_name(s: &[u8]) -> bool {
+    match s.first() {
+        Some(b'a') | Some(b'A') => match s.get(1) {
+            None | Some(b'>') | Some(b'/') => true,
+            Some(b) => b.is_ascii_whitespace(),
+        },
+        _ => false,
+    }
+}
+
+/// Find the end of the tag (after the `>`) starting from index `i` of `s`, skipping over quoted
+/// attribute values like `href="a>b"`. If the tag is not finished, this is the end of `s`.
+fn find_tag_end(s: &[u8], i: usize) -> usize {
+    let mut quote = None;
+    // The last byte that is not whitespace, quotes only start attribute values after `=`
+    let mut previous = 0;
+    for (j, &b) in s.iter().enumerate().skip(i) {
+        match quote {
+            Some(q) if q == b => quote = None,
+            Some(_) => {}
+            None if (b == b'"' || b == b'\'') && previous == b'=' => quote = Some(b),
+            None if b == b'>' => return j + 1,
+            None => {}
+        }
+        if !b.is_ascii_whitespace() {
+            previous = b;
+        }
+    }
+    s.len()
+}
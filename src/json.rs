@@ -0,0 +1,108 @@
+//! Minimal JSON scanning, for only finding links in string values and decoding escapes.
+
+use std::ops::Range;
+
+use memchr::{memchr, memchr2};
+
+use crate::percent::{decode_with, DecodedText};
+
+/// Find the ranges of the contents (without the quotes) of the strings in `s` that are values,
+/// as opposed to object keys.
+///
+/// This is not a full JSON parser. It only tracks whether it's in a string (handling `\"`
+/// escapes), and a string is a key if the next character after it that is not whitespace is a
+/// `:`. A string that is not closed continues until the end of `s`. The ranges are in order and
+/// don't overlap.
+pub(crate) fn find_string_values(s: &str) -> Vec<Range<usize>> {
+    let bytes = s.as_bytes();
+    let mut values = Vec::new();
+    let mut i = 0;
+
+    while let Some(quote) = memchr(b'"', &bytes[i..]) {
+        let start = i + quote + 1;
+        let end = find_string_end(bytes, start);
+        // After the closing quote
+        i = (end + 1).min(bytes.len());
+
+        let is_key = matches!(
+            bytes[i..].iter().find(|b| !b.is_ascii_whitespace()),
+            Some(b':')
+        );
+        if !is_key {
+            values.push(start..end);
+        }
+    }
+    values
+}
+
+/// Find the index of the closing quote of the string starting at index `i` of `s`, or the end
+/// of `s` if the string is not closed.
+fn find_string_end(s: &[u8], mut i: usize) -> usize {
+    while let Some(found) = memchr2(b'"', b'\\', &s[i..]) {
+        let j = i + found;
+        if s[j] == b'"' {
+            return j;
+        }
+        // Skip the escaped character
+        i = (j + 2).min(s.len());
+    }
+    s.len()
+}
+
+/// Decode the escapes `\/` and `\uXXXX` (including surrogate pairs like `\ud83d\ude00`) in the
+/// JSON text `s`, e.g. `http:\/\/example.org` to `http://example.org`.
+///
+/// Other escapes like `\"`, `\\` and `\n` are kept as they are, as are `\u` escapes of `"` and
+/// `\`, so that the strings still end at the same place.
+pub(crate) fn unescape(s: &str) -> DecodedText {
+    decode_with(s, |rest, text| {
+        let bytes = rest.as_bytes();
+        if bytes[0] != b'\\' {
+            return None;
+        }
+        let (c, len) = match bytes.get(1)? {
+            b'/' => ('/', 2),
+            b'u' => unescape_unicode(rest)?,
+            // Keep the escaped character, so that e.g. the `/` of `\\/` is not decoded
+            _ => {
+                let escaped = rest[1..].chars().next()?;
+                text.push('\\');
+                text.push(escaped);
+                return Some(1 + escaped.len_utf8());
+            }
+        };
+        if c == '"' || c == '\\' {
+            return None;
+        }
+        text.push(c);
+        Some(len)
+    })
+}
+
+/// Decode the `\uXXXX` escape at the start of `s`, or the surrogate pair of two escapes. Returns
+/// the character and the length of the escapes.
+fn unescape_unicode(s: &str) -> Option<(char, usize)> {
+    let high = parse_hex4(&s[2..])?;
+    if !(0xD800..0xDC00).contains(&high) {
+        // Lone low surrogates are not characters
+        return std::char::from_u32(high).map(|c| (c, 6));
+    }
+    if !s[6..].starts_with("\\u") {
+        return None;
+    }
+    let low = parse_hex4(&s[8..])?;
+    if !(0xDC00..0xE000).contains(&low) {
+        return None;
+    }
+    let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+    std::char::from_u32(code).map(|c| (c, 12))
+}
+
+/// Parse the 4 hex digits at the start of `s`.
+fn parse_hex4(s: &str) -> Option<u32> {
+    let hex = s.get(..4)?;
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
@@ -102,7 +102,7 @@
 //! This crates makes an effort to respect the various standards, namely:
 //!
 //! * [RFC 3986] and [RFC 3987] for URLs
-//! * [RFC 5321] and [RFC 6531] for emails (except IP addresses and quoting)
+//! * [RFC 5321] and [RFC 6531] for emails (except quoting)
 //!
 //! At the same time, it does not guarantee that the returned links are valid.
 //! If in doubt, it rather returns a link than skipping it.
@@ -123,15 +123,36 @@
 mod chars;
 mod domains;
 mod email;
+mod entities;
+mod error;
 mod finder;
+mod html;
+mod json;
+mod pattern;
+mod percent;
+mod phone;
 mod scanner;
+mod social;
+mod stream;
 mod url;
+mod validate;
 
+pub use crate::error::ScanError;
 pub use crate::finder::Link;
 pub use crate::finder::LinkFinder;
 pub use crate::finder::LinkKind;
 pub use crate::finder::Links;
+pub use crate::finder::OverlapStrategy;
+pub use crate::finder::OwnedLink;
+pub use crate::finder::Segment;
+pub use crate::finder::TerminationReason;
 pub use crate::finder::{Span, Spans};
+pub use crate::finder::{DEFAULT_EMAIL_SCHEMES, DEFAULT_SHORTENER_HOSTS, UNICODE_BRACKET_PAIRS};
+pub use crate::pattern::PatternSuffix;
+pub use crate::phone::PhoneFormat;
+pub use crate::stream::ReaderLinks;
+pub use crate::url::{BalanceSet, CompatMode, SchemeSet, WhitespacePolicy};
+pub use crate::validate::{is_email, is_url, EmailOptions, UrlOptions};
 
 #[cfg(doctest)]
 doc_comment::doctest!("../README.md");
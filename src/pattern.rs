@@ -0,0 +1,74 @@
+use std::ops::Range;
+
+use crate::scanner::Scanner;
+
+/// What follows the prefix of a pattern, see `LinkFinder::add_pattern`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum PatternSuffix {
+    /// One or more ASCII digits, e.g. `123` in `PROJ-123`.
+    Digits,
+    /// One or more ASCII letters or digits, e.g. `a1b2` in `commit:a1b2`.
+    Alphanumeric,
+}
+
+/// A pattern for finding references like `PROJ-123` and linking them to a target URL.
+#[derive(Debug)]
+pub struct Pattern {
+    pub prefix: String,
+    pub suffix: PatternSuffix,
+    pub template: String,
+}
+
+impl Pattern {
+    /// The target of a matched token, by replacing `{}` in the template.
+    pub fn target(&self, token: &str) -> String {
+        self.template.replace("{}", token)
+    }
+
+    fn suffix_allowed(&self, c: char) -> bool {
+        match self.suffix {
+            PatternSuffix::Digits => c.is_ascii_digit(),
+            PatternSuffix::Alphanumeric => c.is_ascii_alphanumeric(),
+        }
+    }
+}
+
+impl Scanner for Pattern {
+    fn scan(&self, s: &str, start: usize) -> Option<Range<usize>> {
+        if !s[start..].starts_with(self.prefix.as_str()) {
+            return None;
+        }
+        // Must be at the start of a word, e.g. not `XPROJ-123`
+        if let Some(c) = s[..start].chars().next_back() {
+            if is_word_char(c) {
+                return None;
+            }
+        }
+
+        let suffix_start = start + self.prefix.len();
+        let suffix_len = s[suffix_start..]
+            .find(|c| !self.suffix_allowed(c))
+            .unwrap_or(s.len() - suffix_start);
+        if suffix_len == 0 {
+            return None;
+        }
+
+        let end = suffix_start + suffix_len;
+        // Must be at the end of a word, e.g. not `PROJ-123abc` or `PROJ-123@example.org`
+        let mut after = s[end..].chars();
+        match after.next() {
+            Some(c) if is_word_char(c) || c == '@' || c == '-' => return None,
+            // Part of a domain or path, e.g. `PROJ-123.example.org`
+            Some('.') | Some('/') if matches!(after.next(), Some(c) if is_word_char(c)) => {
+                return None
+            }
+            _ => {}
+        }
+        Some(Range { start, end })
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
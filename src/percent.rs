@@ -0,0 +1,123 @@
+//! Percent-decoding as defined in RFC 3986, section 2.1.
+
+use std::borrow::Cow;
+
+use crate::chars::fullwidth_to_ascii;
+
+/// Decode `%HH` sequences in `s`. Sequences that are not followed by two hex digits are kept as
+/// they are. If the decoded bytes are not valid UTF-8, invalid sequences are replaced with
+/// U+FFFD.
+pub(crate) fn percent_decode(s: &str) -> Cow<'_, str> {
+    if !s.contains('%') {
+        return Cow::Borrowed(s);
+    }
+
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = decode_hex_pair(&bytes[i + 1..]) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    match String::from_utf8(decoded) {
+        Ok(decoded) => Cow::Owned(decoded),
+        Err(e) => Cow::Owned(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+    }
+}
+
+/// Decode the first two bytes of `s` as a hex number, if they are hex digits.
+pub(crate) fn decode_hex_pair(s: &[u8]) -> Option<u8> {
+    if s.len() < 2 {
+        return None;
+    }
+    let high = (s[0] as char).to_digit(16)?;
+    let low = (s[1] as char).to_digit(16)?;
+    Some((high * 16 + low) as u8)
+}
+
+/// Text with the percent-encoded ASCII characters decoded (or fullwidth forms or JSON escapes, see
+/// `decode_with`), and the offsets of the decoded text in the original text.
+#[derive(Debug)]
+pub(crate) struct DecodedText {
+    pub text: String,
+    // The index in the original text for each byte of the decoded text, plus one for the end
+    offsets: Vec<usize>,
+}
+
+impl DecodedText {
+    /// The index in the original text for the index `i` of the decoded text.
+    pub fn original(&self, i: usize) -> usize {
+        self.offsets[i]
+    }
+
+    /// Decode the decoded text further with `decode`, keeping the offsets in the original text.
+    pub fn and_then(self, decode: impl FnOnce(&str) -> DecodedText) -> DecodedText {
+        let next = decode(&self.text);
+        let offsets = next.offsets.iter().map(|&i| self.offsets[i]).collect();
+        DecodedText {
+            text: next.text,
+            offsets,
+        }
+    }
+}
+
+/// Decode `%HH` sequences in `s` that are ASCII characters, e.g. `%3A` but not `%C3%A9` (these
+/// are kept as they are, so that the decoded text is always valid UTF-8).
+pub(crate) fn percent_decode_ascii(s: &str) -> DecodedText {
+    decode_with(s, |rest, text| {
+        let rest = rest.as_bytes();
+        if rest[0] != b'%' {
+            return None;
+        }
+        let byte = decode_hex_pair(&rest[1..]).filter(u8::is_ascii)?;
+        text.push(byte as char);
+        Some(3)
+    })
+}
+
+/// Replace fullwidth forms in `s` with their ASCII characters, e.g. `ｈｔｔｐ：／／` with
+/// `http://`.
+pub(crate) fn normalize_fullwidth(s: &str) -> DecodedText {
+    decode_with(s, |rest, text| {
+        let c = rest.chars().next()?;
+        text.push(fullwidth_to_ascii(c)?);
+        Some(c.len_utf8())
+    })
+}
+
+/// Decode `s` with `decode`, which is called with the rest of the text at each character. If it
+/// decodes something there, it pushes the decoded text and returns the length of the text that
+/// was decoded. Otherwise it returns `None` and the character is kept as it is.
+pub(crate) fn decode_with(
+    s: &str,
+    decode: impl Fn(&str, &mut String) -> Option<usize>,
+) -> DecodedText {
+    let mut text = String::with_capacity(s.len());
+    let mut offsets = Vec::with_capacity(s.len() + 1);
+    let mut i = 0;
+    while i < s.len() {
+        match decode(&s[i..], &mut text) {
+            Some(len) => {
+                // All of the decoded text is at the start of the encoded text
+                offsets.resize(text.len(), i);
+                i += len;
+            }
+            None => {
+                let c = s[i..].chars().next().unwrap();
+                text.push(c);
+                offsets.extend(i..i + c.len_utf8());
+                i += c.len_utf8();
+            }
+        }
+    }
+    offsets.push(s.len());
+    DecodedText { text, offsets }
+}
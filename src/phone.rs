@@ -0,0 +1,168 @@
+use std::ops::Range;
+
+use crate::scanner::Scanner;
+
+/// A format of phone numbers to find, see `LinkFinder::phone_formats`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum PhoneFormat {
+    /// E.164 numbers like "+14155552671": a `+` followed by 8 to 15 digits, without separators.
+    E164,
+    /// North American numbers like "(415) 555-2671", "415-555-2671" or "+1 415.555.2671".
+    Nanp,
+    /// International numbers like "+44 20 7946 0958": a `+` followed by 8 to 15 digits, in groups
+    /// separated by a single space, `-` or `.`. Groups after the country code can be in
+    /// parentheses, like in "+1 (415) 555-2671".
+    International,
+}
+
+/// Scan for phone numbers starting from the trigger character, which is the first character of
+/// the number (`+`, `(` or a digit).
+pub struct PhoneScanner {
+    pub formats: Vec<PhoneFormat>,
+    /// Only find numbers that start with `+` or `(`.
+    pub conservative: bool,
+}
+
+impl Scanner for PhoneScanner {
+    fn scan(&self, s: &str, start: usize) -> Option<Range<usize>> {
+        // Must not continue a word or number, e.g. "abc415-555-2671" or "1.415-555-2671"
+        if let Some(c) = s[..start].chars().next_back() {
+            if c.is_alphanumeric() || "+-._/@".contains(c) {
+                return None;
+            }
+        }
+
+        let bytes = &s.as_bytes()[start..];
+        if self.conservative && !matches!(bytes.first(), Some(b'+') | Some(b'(')) {
+            return None;
+        }
+        let len = self
+            .formats
+            .iter()
+            .filter_map(|format| match format {
+                PhoneFormat::E164 => e164_len(bytes),
+                PhoneFormat::Nanp => nanp_len(bytes),
+                PhoneFormat::International => international_len(bytes),
+            })
+            .max()?;
+
+        let end = start + len;
+        let mut after = s[end..].chars();
+        match after.next() {
+            Some(c) if c.is_alphanumeric() || c == '_' || c == '@' => None,
+            // Part of a longer number, e.g. "415-555-2671-22"
+            Some('-') | Some('.') if matches!(after.next(), Some(c) if c.is_ascii_digit()) => None,
+            _ => Some(Range { start, end }),
+        }
+    }
+}
+
+fn e164_len(b: &[u8]) -> Option<usize> {
+    if b.first() != Some(&b'+') {
+        return None;
+    }
+    let digits = count_digits(&b[1..]);
+    if (8..=15).contains(&digits) && b[1] != b'0' {
+        Some(1 + digits)
+    } else {
+        None
+    }
+}
+
+fn international_len(b: &[u8]) -> Option<usize> {
+    if b.first() != Some(&b'+') || b.get(1) == Some(&b'0') {
+        return None;
+    }
+    let mut i = 1;
+    let mut total = 0;
+    loop {
+        // The country code can't be in parentheses
+        let parenthesized = i > 1 && b[i] == b'(';
+        if parenthesized {
+            i += 1;
+        }
+        let digits = count_digits(&b[i..]);
+        if digits == 0 {
+            return None;
+        }
+        i += digits;
+        total += digits;
+        if parenthesized {
+            if b.get(i) != Some(&b')') {
+                return None;
+            }
+            i += 1;
+        }
+        match b.get(i..i + 2) {
+            Some(&[sep, next]) if is_separator(sep) && (next.is_ascii_digit() || next == b'(') => {
+                i += 1
+            }
+            _ => break,
+        }
+    }
+    if (8..=15).contains(&total) {
+        Some(i)
+    } else {
+        None
+    }
+}
+
+fn nanp_len(b: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    // Optional country code
+    if b.starts_with(b"+1") || b.starts_with(b"1") {
+        i = if b[0] == b'+' { 2 } else { 1 };
+        if !matches!(b.get(i), Some(&sep) if is_separator(sep)) {
+            return None;
+        }
+        i += 1;
+    }
+
+    // Area code, either "(415) " or "415-"
+    if b.get(i) == Some(&b'(') {
+        i = group(b, i + 1, 3, true)?;
+        if b.get(i) != Some(&b')') {
+            return None;
+        }
+        i += 1;
+        if b.get(i) == Some(&b' ') {
+            i += 1;
+        }
+    } else {
+        i = group(b, i, 3, true)?;
+        i = separator(b, i)?;
+    }
+
+    i = group(b, i, 3, true)?;
+    i = separator(b, i)?;
+    group(b, i, 4, false)
+}
+
+/// Match exactly `len` digits at `i`, and return the index after them. NANP area codes and
+/// exchanges can't start with 0 or 1 (`restricted`).
+fn group(b: &[u8], i: usize, len: usize, restricted: bool) -> Option<usize> {
+    let digits = b.get(i..i + len)?;
+    if !digits.iter().all(u8::is_ascii_digit) || restricted && digits[0] < b'2' {
+        return None;
+    }
+    if matches!(b.get(i + len), Some(d) if d.is_ascii_digit()) {
+        return None;
+    }
+    Some(i + len)
+}
+
+fn separator(b: &[u8], i: usize) -> Option<usize> {
+    match b.get(i) {
+        Some(&sep) if is_separator(sep) => Some(i + 1),
+        _ => None,
+    }
+}
+
+fn count_digits(b: &[u8]) -> usize {
+    b.iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+fn is_separator(b: u8) -> bool {
+    b == b' ' || b == b'-' || b == b'.'
+}
@@ -1,5 +1,20 @@
 use std::ops::Range;
 
+use crate::finder::TerminationReason;
+use crate::url::termination_after;
+
 pub trait Scanner {
     fn scan(&self, s: &str, trigger_index: usize) -> Option<Range<usize>>;
+
+    /// Like `scan`, but also returns why the link ended. Scanners that scan the end of the link
+    /// character by character record it there, the others use the character after the link.
+    fn scan_with_reason(
+        &self,
+        s: &str,
+        trigger_index: usize,
+    ) -> Option<(Range<usize>, TerminationReason)> {
+        let range = self.scan(s, trigger_index)?;
+        let reason = termination_after(s, &range);
+        Some((range, reason))
+    }
 }
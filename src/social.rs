@@ -0,0 +1,60 @@
+use std::ops::Range;
+
+use crate::scanner::Scanner;
+
+/// Scan for hashtags like "#rustlang" starting from the trigger character "#".
+///
+/// The tag consists of letters, digits and underscores, and can't be all digits (e.g. "#1").
+pub struct HashtagScanner;
+
+/// Scan for mentions like "@user" starting from the trigger character "@".
+///
+/// The name consists of letters, digits and underscores.
+pub struct MentionScanner;
+
+impl Scanner for HashtagScanner {
+    fn scan(&self, s: &str, hash: usize) -> Option<Range<usize>> {
+        // Not after `&`, that's a numeric character reference like "&#39;"
+        if s[..hash].ends_with('&') {
+            return None;
+        }
+        let end = find_tag_end(s, hash)?;
+        if s[hash + 1..end].bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some(Range { start: hash, end })
+    }
+}
+
+impl Scanner for MentionScanner {
+    fn scan(&self, s: &str, at: usize) -> Option<Range<usize>> {
+        let end = find_tag_end(s, at)?;
+        // Looks like an email address or another mention, e.g. "@a@b"
+        if s[end..].starts_with('@') {
+            return None;
+        }
+        Some(Range { start: at, end })
+    }
+}
+
+/// Find the end of the tag after the sigil at `sigil`, which must be at the start of a word.
+fn find_tag_end(s: &str, sigil: usize) -> Option<usize> {
+    if let Some(c) = s[..sigil].chars().next_back() {
+        if is_tag_char(c) {
+            return None;
+        }
+    }
+    let start = sigil + 1;
+    let len = s[start..]
+        .find(|c| !is_tag_char(c))
+        .unwrap_or(s.len() - start);
+    if len == 0 {
+        None
+    } else {
+        Some(start + len)
+    }
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
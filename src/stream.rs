@@ -0,0 +1,141 @@
+//! Finding links in input that is read in chunks, e.g. from a file or a socket.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{ErrorKind, Read};
+use std::str;
+
+use crate::error::ScanError;
+use crate::finder::{LinkFinder, OwnedLink};
+
+/// How many bytes are read from the reader at once.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Iterator for finding links in input from a reader, see `LinkFinder::links_from_reader`.
+pub struct ReaderLinks<'f, R> {
+    finder: &'f LinkFinder,
+    reader: R,
+    // Text that was read but not scanned yet, because a link could continue in the next chunk
+    buffer: String,
+    // Bytes at the end of the last chunk that are not a complete UTF-8 character yet
+    incomplete: Vec<u8>,
+    // The offset of `buffer` in the whole input
+    offset: usize,
+    found: VecDeque<OwnedLink>,
+    // The error that ended the input, returned after the links found before it
+    error: Option<ScanError>,
+    done: bool,
+}
+
+impl<'f, R: Read> ReaderLinks<'f, R> {
+    pub(crate) fn new(finder: &'f LinkFinder, reader: R) -> ReaderLinks<'f, R> {
+        ReaderLinks {
+            finder,
+            reader,
+            buffer: String::new(),
+            incomplete: Vec::new(),
+            offset: 0,
+            found: VecDeque::new(),
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Read the next chunk and scan the text in it that can't be part of a link that continues
+    /// in later chunks. Returns `false` at the end of the input.
+    fn read_chunk(&mut self) -> Result<bool, ScanError> {
+        let mut chunk = [0; CHUNK_SIZE];
+        let read = loop {
+            match self.reader.read(&mut chunk) {
+                Ok(read) => break read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        if read == 0 {
+            let len = self.buffer.len();
+            self.scan(len);
+            if !self.incomplete.is_empty() {
+                // The input ended in the middle of a character
+                return Err(str::from_utf8(&self.incomplete).unwrap_err().into());
+            }
+            return Ok(false);
+        }
+
+        self.incomplete.extend_from_slice(&chunk[..read]);
+        let (valid, error) = match str::from_utf8(&self.incomplete) {
+            Ok(s) => (s.len(), None),
+            // Only the last character is cut off, the rest of it is in the next chunk
+            Err(e) if e.error_len().is_none() => (e.valid_up_to(), None),
+            Err(e) => (e.valid_up_to(), Some(e)),
+        };
+        let scanned = self.buffer.len();
+        // Checked above
+        self.buffer
+            .push_str(str::from_utf8(&self.incomplete[..valid]).unwrap());
+        self.incomplete.drain(..valid);
+
+        if let Some(e) = error {
+            // The input ends at the invalid bytes, the text before them is still scanned
+            let len = self.buffer.len();
+            self.scan(len);
+            return Err(e.into());
+        }
+
+        // Links can't continue across the last boundary, everything before it can be scanned
+        let finder = self.finder;
+        if finder.needs_whole_input() {
+            return Ok(true);
+        }
+        if let Some(boundary) = self.buffer.as_bytes()[scanned..]
+            .iter()
+            .rposition(|&b| finder.is_link_boundary(b))
+        {
+            self.scan(scanned + boundary);
+        }
+        Ok(true)
+    }
+
+    /// Find the links in the first `len` bytes of the buffer and remove them from it.
+    fn scan(&mut self, len: usize) {
+        let offset = self.offset;
+        let links = self.finder.links(&self.buffer[..len]);
+        self.found
+            .extend(links.map(|link| OwnedLink::with_offset(link, offset)));
+        self.buffer.drain(..len);
+        self.offset += len;
+    }
+}
+
+impl<'f, R: Read> Iterator for ReaderLinks<'f, R> {
+    type Item = Result<OwnedLink, ScanError>;
+
+    fn next(&mut self) -> Option<Result<OwnedLink, ScanError>> {
+        loop {
+            if let Some(link) = self.found.pop_front() {
+                return Some(Ok(link));
+            }
+            if self.done {
+                return self.error.take().map(Err);
+            }
+            match self.read_chunk() {
+                Ok(true) => {}
+                Ok(false) => self.done = true,
+                Err(e) => {
+                    // Links after an error are not found, the input is incomplete
+                    self.done = true;
+                    self.error = Some(e);
+                }
+            }
+        }
+    }
+}
+
+impl<'f, R> fmt::Debug for ReaderLinks<'f, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReaderLinks")
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
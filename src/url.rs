@@ -1,8 +1,13 @@
 use std::char;
 use std::ops::Range;
+use std::sync::Arc;
 
-use crate::chars::is_email_local_char;
-use crate::domains::find_authority_end;
+use crate::chars::{is_email_local_char, is_emoji, is_symbol};
+use crate::domains::{find_authority_end, is_ipv4, parse_numeric_ipv4, valid_tld};
+use crate::email::EmailScanner;
+use crate::entities::parse_entity;
+use crate::finder::TerminationReason;
+use crate::percent::decode_hex_pair;
 use crate::scanner::Scanner;
 
 /// Minimum valid URL length
@@ -16,31 +21,438 @@ const MIN_URL_LENGTH: usize = 4;
 
 const QUOTES: &[char] = &['\'', '\"'];
 
+/// The default maximum length of URL schemes, see `LinkFinder::url_max_scheme_len`.
+pub(crate) const DEFAULT_MAX_SCHEME_LEN: usize = 64;
+
+/// The kinds of brackets that are balanced in the path, query and fragment of URLs.
+///
+/// If a kind of bracket is balanced, a closing bracket is only included in an URL if there was a
+/// matching opening bracket before it, e.g. `http://example.org/a(b)` includes the `)` but
+/// `(http://example.org/)` doesn't. If a kind of bracket is not balanced, both the opening and
+/// closing brackets end the URL instead, e.g. `http://example.org/a[b]` ends before the `[`.
+///
+/// By default all kinds of brackets are balanced.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BalanceSet {
+    round: bool,
+    square: bool,
+    curly: bool,
+}
+
+impl BalanceSet {
+    /// Balance all kinds of brackets: `()`, `[]` and `{}`.
+    pub fn all() -> BalanceSet {
+        BalanceSet {
+            round: true,
+            square: true,
+            curly: true,
+        }
+    }
+
+    /// Don't balance any brackets, so that all of them end URLs.
+    pub fn none() -> BalanceSet {
+        BalanceSet {
+            round: false,
+            square: false,
+            curly: false,
+        }
+    }
+
+    /// Set whether round brackets `()` are balanced.
+    pub fn round(mut self, value: bool) -> BalanceSet {
+        self.round = value;
+        self
+    }
+
+    /// Set whether square brackets `[]` are balanced.
+    pub fn square(mut self, value: bool) -> BalanceSet {
+        self.square = value;
+        self
+    }
+
+    /// Set whether curly brackets `{}` are balanced.
+    pub fn curly(mut self, value: bool) -> BalanceSet {
+        self.curly = value;
+        self
+    }
+}
+
+impl Default for BalanceSet {
+    fn default() -> Self {
+        BalanceSet::all()
+    }
+}
+
+/// Which whitespace characters end URLs, see `LinkFinder::url_terminating_whitespace`.
+///
+/// ASCII whitespace always ends URLs, regardless of the policy.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum WhitespacePolicy {
+    /// All Unicode whitespace ends URLs, e.g. NO-BREAK SPACE (U+00A0). This is the default.
+    All,
+    /// Only ASCII whitespace ends URLs. Other whitespace is handled like other non-ASCII
+    /// characters, see `LinkFinder::url_can_be_iri`.
+    AsciiOnly,
+    /// The specified characters end URLs (in addition to ASCII whitespace), e.g.
+    /// `vec!['\u{A0}', '\u{200B}']` to also end URLs at ZERO WIDTH SPACE.
+    Custom(Vec<char>),
+}
+
+impl WhitespacePolicy {
+    pub(crate) fn terminates(&self, c: char) -> bool {
+        match self {
+            WhitespacePolicy::All => c.is_whitespace(),
+            WhitespacePolicy::AsciiOnly => c.is_ascii_whitespace(),
+            WhitespacePolicy::Custom(chars) => c.is_ascii_whitespace() || chars.contains(&c),
+        }
+    }
+}
+
+// `#[default]` on enum variants requires a newer Rust than the minimum supported version
+#[allow(clippy::derivable_impls)]
+impl Default for WhitespacePolicy {
+    fn default() -> Self {
+        WhitespacePolicy::All
+    }
+}
+
+/// A profile of edge-case behaviors for where URLs end, see `LinkFinder::compat_mode`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CompatMode {
+    /// The behavior of this crate. This is the default.
+    Linkify,
+    /// Closer to the Java library autolink-java, whose URL scanner only treats `?`, `!`, `.`,
+    /// `,`, `:` and `;` as trailing punctuation. So `*` can be at the end of URLs, e.g.
+    /// `https://example.org/*` is linked as a whole instead of without the `*`.
+    AutolinkJava,
+}
+
+// `#[default]` on enum variants requires a newer Rust than the minimum supported version
+#[allow(clippy::derivable_impls)]
+impl Default for CompatMode {
+    fn default() -> Self {
+        CompatMode::Linkify
+    }
+}
+
+/// The schemes that require a host by default, see `SchemeSet::require_host`.
+const SCHEMES_REQUIRING_HOST: &[&str] = &["https", "http", "ftp", "ftps", "ssh"];
+
+/// Browser-internal schemes that are used without `//`, see `SchemeSet::browser_schemes`.
+const BROWSER_OPAQUE_SCHEMES: &[&str] = &["about", "view-source"];
+
+/// The URL schemes that are found and how they're handled, see `LinkFinder::with_schemes`.
+///
+/// A set is immutable once it's shared with an `Arc`, so it can be built once and then used for
+/// many finders without building it again. Schemes are compared ignoring ASCII case.
+///
+/// By default all schemes are found, and `http`, `https`, `ftp`, `ftps` and `ssh` URLs must have
+/// a host that is a domain or an IP address. All URLs need `//` after the scheme (like
+/// `https://example.org`), unless the scheme is marked with `slashes_optional`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchemeSet {
+    allowed: Option<Vec<String>>,
+    require_host: Vec<String>,
+    slashes_optional: Vec<String>,
+    kept_sub_delims: Vec<(String, Vec<char>)>,
+}
+
+impl SchemeSet {
+    /// Create a set that allows all schemes, with the default schemes that require a host.
+    pub fn new() -> SchemeSet {
+        SchemeSet {
+            allowed: None,
+            require_host: SCHEMES_REQUIRING_HOST
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            slashes_optional: Vec::new(),
+            kept_sub_delims: Vec::new(),
+        }
+    }
+
+    /// Only find URLs with one of the specified schemes, e.g. `&["http", "https"]`.
+    pub fn allow_only(mut self, schemes: &[&str]) -> SchemeSet {
+        self.allowed = Some(schemes.iter().map(|s| s.to_ascii_lowercase()).collect());
+        self
+    }
+
+    /// Require that URLs with the specified schemes have a host that is a domain or an IP
+    /// address, in addition to the schemes that already require one.
+    pub fn require_host(mut self, schemes: &[&str]) -> SchemeSet {
+        self.require_host
+            .extend(schemes.iter().map(|s| s.to_ascii_lowercase()));
+        self
+    }
+
+    /// Also find URLs with the specified schemes that don't have `//` after the scheme, e.g.
+    /// `&["urn", "mailto", "about"]` for `urn:isbn:0451450523`, `mailto:foo@example.org` and
+    /// `about:blank`.
+    ///
+    /// The rest of the URL is scanned like a path, so it ends at whitespace and trailing
+    /// punctuation is not included. This doesn't apply to schemes that require a host (e.g.
+    /// `http`), those always need `//`.
+    pub fn slashes_optional(mut self, schemes: &[&str]) -> SchemeSet {
+        self.slashes_optional
+            .extend(schemes.iter().map(|s| s.to_ascii_lowercase()));
+        self
+    }
+
+    /// Set which sub-delimiters (`!$&'()*+,;=`) are part of the authority of URLs with the
+    /// specified schemes, e.g. `&[';', ',']` for `custom://a;b,c;`.
+    ///
+    /// By default, the authority of URLs with schemes that don't require a host (see
+    /// `require_host`) can contain sub-delimiters, but not end with them, e.g. the `;` of
+    /// `custom://a;` is not included. For the specified schemes, the sub-delimiters in `chars`
+    /// can also be at the end of the authority, and the other sub-delimiters end it. This doesn't
+    /// apply to schemes that require a host.
+    pub fn keep_sub_delims(mut self, schemes: &[&str], chars: &[char]) -> SchemeSet {
+        self.kept_sub_delims.extend(
+            schemes
+                .iter()
+                .map(|scheme| (scheme.to_ascii_lowercase(), chars.to_vec())),
+        );
+        self
+    }
+
+    /// Also find the browser-internal URLs that don't have `//` after the scheme, like
+    /// `about:config` and `view-source:https://example.org/` (see `slashes_optional`).
+    ///
+    /// Browser URLs with `//` like `chrome://flags` or `edge://settings` are already found by
+    /// default. This is opt-in because `about:` is common in plain text, e.g. "Think about:".
+    pub fn browser_schemes(self) -> SchemeSet {
+        self.slashes_optional(BROWSER_OPAQUE_SCHEMES)
+    }
+
+    /// Whether URLs with the scheme are found.
+    pub fn is_allowed(&self, scheme: &str) -> bool {
+        match &self.allowed {
+            Some(allowed) => contains_scheme(allowed, scheme),
+            None => true,
+        }
+    }
+
+    /// Whether URLs with the scheme must have a host that is a domain or an IP address.
+    pub fn requires_host(&self, scheme: &str) -> bool {
+        contains_scheme(&self.require_host, scheme)
+    }
+
+    /// The sub-delimiters that are part of the authority of URLs with the scheme, if they're set
+    /// with `keep_sub_delims`.
+    pub fn kept_sub_delims(&self, scheme: &str) -> Option<&[char]> {
+        self.kept_sub_delims
+            .iter()
+            .rev()
+            .find(|(s, _)| s.eq_ignore_ascii_case(scheme))
+            .map(|(_, chars)| chars.as_slice())
+    }
+
+    /// Whether URLs with the scheme must have `//` after the scheme.
+    pub fn requires_slashes(&self, scheme: &str) -> bool {
+        !contains_scheme(&self.slashes_optional, scheme) || self.requires_host(scheme)
+    }
+}
+
+impl Default for SchemeSet {
+    fn default() -> Self {
+        SchemeSet::new()
+    }
+}
+
+fn contains_scheme(schemes: &[String], scheme: &str) -> bool {
+    schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme))
+}
+
+/// Options for scanning URLs, shared by `UrlScanner` and `DomainScanner`.
+#[derive(Clone, Debug, Default)]
+pub struct UrlConfig {
+    pub iri_parsing_enabled: bool,
+    pub balance: BalanceSet,
+    pub decode_html_entities: bool,
+    pub trim_trailing_symbols: bool,
+    pub reject_version_tokens: bool,
+    pub whitespace: WhitespacePolicy,
+    pub compat: CompatMode,
+    pub max_bracket_depth: Option<usize>,
+    pub reject_emoji_host: bool,
+    /// Whether URLs must have a path, query or fragment after the authority.
+    pub require_path: bool,
+    /// Whether URLs with a scheme that are enclosed in `<>` or `""` can contain spaces.
+    pub spaces_when_delimited: bool,
+    /// Whether a `%` that is not followed by two hex digits ends the path.
+    pub strict_percent: bool,
+    /// The maximum length of schemes, `None` for no limit.
+    pub max_scheme_len: Option<usize>,
+    /// The minimum length of the host of URLs without a scheme (including scheme-relative ones),
+    /// in characters.
+    pub min_host_length: usize,
+    /// Whether IPv4 addresses with 2 or 3 parts are allowed in URLs with a scheme.
+    pub allow_short_ipv4: bool,
+    /// Whether IPv4 addresses in decimal, octal or hexadecimal forms like `2130706433` are
+    /// allowed in URLs with a scheme.
+    pub allow_numeric_ipv4: bool,
+    /// Whether the host of URLs with a scheme that requires a host must have a dot (or be an
+    /// IPv6 address).
+    pub require_dotted_host: bool,
+    /// Additional characters that end the path, query and fragment.
+    pub path_terminators: Vec<char>,
+    /// Trailing punctuation that is kept at the end of URLs, per scheme.
+    pub keep_trailing: Vec<(String, Vec<char>)>,
+    /// Additional pairs of brackets that are balanced, as `(open, close)`.
+    pub balance_pairs: Vec<(char, char)>,
+    /// Hosts that are always accepted, in lowercase.
+    pub known_hosts: Vec<String>,
+    /// The schemes, or `None` for the defaults (avoids building a set for each finder).
+    pub schemes: Option<Arc<SchemeSet>>,
+}
+
+impl UrlConfig {
+    /// The length of the known host at the start of `s`, if it starts with one.
+    fn known_host_len(&self, s: &str) -> Option<usize> {
+        self.known_hosts
+            .iter()
+            .find(|host| {
+                matches!(s.get(..host.len()), Some(start) if start.eq_ignore_ascii_case(host))
+                    && !continues_host(&s[host.len()..])
+            })
+            .map(|host| host.len())
+    }
+
+    /// Whether opening another bracket at the current `depth` exceeds the maximum depth.
+    fn exceeds_bracket_depth(&self, depth: i32) -> bool {
+        match self.max_bracket_depth {
+            Some(max) => depth as usize >= max,
+            None => false,
+        }
+    }
+
+    /// The trailing punctuation that is kept at the end of URLs with `scheme`.
+    fn keep_trailing(&self, scheme: &str) -> &[char] {
+        self.keep_trailing
+            .iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(scheme))
+            .map(|(_, chars)| chars.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn scheme_allowed(&self, scheme: &str) -> bool {
+        match &self.schemes {
+            Some(schemes) => schemes.is_allowed(scheme),
+            None => true,
+        }
+    }
+
+    fn scheme_requires_slashes(&self, scheme: &str) -> bool {
+        match &self.schemes {
+            Some(schemes) => schemes.requires_slashes(scheme),
+            None => true,
+        }
+    }
+
+    fn scheme_requires_host(&self, scheme: &str) -> bool {
+        match &self.schemes {
+            Some(schemes) => schemes.requires_host(scheme),
+            None => scheme_requires_host(scheme),
+        }
+    }
+
+    fn kept_sub_delims(&self, scheme: &str) -> Option<&[char]> {
+        self.schemes
+            .as_ref()
+            .and_then(|schemes| schemes.kept_sub_delims(scheme))
+    }
+
+    /// Whether `c` is one of the brackets of `balance_pairs`.
+    fn is_pair_bracket(&self, c: char) -> bool {
+        self.balance_pairs
+            .iter()
+            .any(|&(open, close)| c == open || c == close)
+    }
+
+    /// Adjust the end of the authority in `s`, e.g. to not include trailing symbols.
+    fn trim_authority_end(&self, s: &str, end: usize) -> usize {
+        // The authority already ends at the whitespace of the policy, but custom ASCII
+        // characters can end it earlier
+        let end = match &self.whitespace {
+            WhitespacePolicy::Custom(chars) => s[..end].find(&chars[..]).unwrap_or(end),
+            _ => end,
+        };
+        // Brackets can't be part of the host
+        let end = s[..end].find(|c| self.is_pair_bracket(c)).unwrap_or(end);
+        let end = if self.reject_emoji_host {
+            s[..end].find(is_emoji).unwrap_or(end)
+        } else {
+            end
+        };
+        if self.trim_trailing_symbols {
+            s[..end].trim_end_matches(is_symbol).len()
+        } else {
+            end
+        }
+    }
+}
+
 /// Scan for URLs starting from the trigger character ":" (requires "://").
 ///
 /// Based on RFC 3986.
 pub struct UrlScanner {
-    pub iri_parsing_enabled: bool,
+    pub config: UrlConfig,
 }
 
 /// Scan for plain domains (without scheme) such as `test.com` or `test.com/hi-there`.
 pub struct DomainScanner {
-    pub iri_parsing_enabled: bool,
+    pub config: UrlConfig,
+}
+
+/// Scan for bare domain names such as `example.com` (without scheme, port or path), see
+/// `LinkKind::Domain`.
+pub struct DomainNameScanner {
+    pub config: UrlConfig,
+}
+
+/// Scan for URLs with a known host such as `wiki/path` (without scheme), starting from the first
+/// character of the host.
+pub struct KnownHostScanner {
+    pub config: UrlConfig,
+}
+
+/// Scan for SCP-like SSH URLs such as `git@example.org:owner/repo.git`, starting from the trigger
+/// character "@".
+pub struct ScpScanner {
+    pub config: UrlConfig,
+    pub email_scanner: EmailScanner,
+}
+
+/// Scan for scheme-relative URLs such as `//example.org/path`, starting from the trigger
+/// character "/".
+pub struct SchemeRelativeScanner {
+    pub config: UrlConfig,
 }
 
 impl Scanner for UrlScanner {
+    fn scan(&self, s: &str, i: usize) -> Option<Range<usize>> {
+        self.scan_with_reason(s, i).map(|(range, _)| range)
+    }
+
     /// Scan for an URL at the given separator index in the string.
     ///
     /// Returns `None` if none was found.
-    fn scan(&self, s: &str, separator: usize) -> Option<Range<usize>> {
+    fn scan_with_reason(
+        &self,
+        s: &str,
+        separator: usize,
+    ) -> Option<(Range<usize>, TerminationReason)> {
         // There must be something before separator for scheme
         if separator == 0 {
             return None;
         }
+        let text = s;
 
         if !s[separator..].starts_with("://") {
-            // We only support schemes with authority, not things like `myscheme:mything`.
-            return None;
+            // Without authority, like `myscheme:mything`, only for schemes that allow it
+            return self.scan_without_slashes(s, separator);
         }
 
         let after_separator = separator + "://".len();
@@ -50,27 +462,67 @@ impl Scanner for UrlScanner {
             return None;
         }
 
-        if let (Some(start), quote) = find_scheme_start(&s[0..separator]) {
+        if let (Some(start), quote) =
+            find_scheme_start(&s[0..separator], self.config.max_scheme_len)
+        {
             let scheme = &s[start..separator];
+            if !self.config.scheme_allowed(scheme) {
+                return None;
+            }
             let s = &s[after_separator..];
 
-            let require_host = scheme_requires_host(scheme);
+            let require_host =
+                self.config.scheme_requires_host(scheme) && self.config.known_host_len(s).is_none();
 
-            if let (Some(after_authority), _) =
-                find_authority_end(s, true, require_host, true, self.iri_parsing_enabled)
-            {
-                if let Some(end) =
-                    find_url_end(&s[after_authority..], quote, self.iri_parsing_enabled)
+            let (authority_end, _) = find_authority_end(
+                s,
+                true,
+                require_host,
+                true,
+                self.config.iri_parsing_enabled,
+                self.config.allow_short_ipv4,
+                if require_host {
+                    None
+                } else {
+                    self.config.kept_sub_delims(scheme)
+                },
+                &self.config.whitespace,
+            );
+            let authority_end = match authority_end {
+                None if require_host && self.config.allow_numeric_ipv4 => {
+                    find_numeric_ipv4_authority_end(s)
+                }
+                authority_end => authority_end,
+            };
+            if let Some(after_authority) = authority_end {
+                let after_authority = self.config.trim_authority_end(s, after_authority);
+                if require_host
+                    && self.config.require_dotted_host
+                    && !is_dotted_host(&s[..after_authority])
+                {
+                    return None;
+                }
+                let keep_trailing = self.config.keep_trailing(scheme);
+                if let Some((end, reason)) =
+                    find_url_end(&s[after_authority..], quote, keep_trailing, &self.config)
                 {
                     if after_authority == 0 && end == 0 {
                         return None;
                     }
+                    let (end, reason) = if end == 0 {
+                        // Kept punctuation right after the authority, e.g. in `slack://x!`
+                        let rest = &s[after_authority..];
+                        let kept = rest.trim_start_matches(keep_trailing);
+                        (rest.len() - kept.len(), termination_at(kept, quote))
+                    } else {
+                        (end, reason)
+                    };
 
                     let range = Range {
                         start,
                         end: after_separator + after_authority + end,
                     };
-                    return Some(range);
+                    return Some(self.extend_delimited(text, range, reason));
                 }
             }
         }
@@ -79,26 +531,180 @@ impl Scanner for UrlScanner {
     }
 }
 
-impl Scanner for DomainScanner {
+impl UrlScanner {
+    /// Extend the URL at `range` in `s` over spaces up to the closing delimiter if it's enclosed
+    /// in `<>` or `""`, e.g. to `http://example.org/a b` for `<http://example.org/a b>` (see
+    /// `UrlConfig::spaces_when_delimited`). This includes trailing punctuation that was trimmed
+    /// before a space, e.g. the `.` in `<http://example.org/a. b>`.
+    fn extend_delimited(
+        &self,
+        s: &str,
+        range: Range<usize>,
+        reason: TerminationReason,
+    ) -> (Range<usize>, TerminationReason) {
+        if !self.config.spaces_when_delimited {
+            return (range, reason);
+        }
+        let (close, close_reason) = match s[..range.start].chars().next_back() {
+            Some('<') => ('>', TerminationReason::InvalidCharacter),
+            Some('"') => ('"', TerminationReason::Quote),
+            _ => return (range, reason),
+        };
+        let rest = &s[range.end..];
+        // The closing delimiter must be on the same line, and there can't be characters that
+        // are never part of URLs before it
+        let len = rest
+            .find(|c: char| {
+                c == close
+                    || c != ' ' && (c.is_whitespace() || c.is_control() || "<>\"`".contains(c))
+            })
+            .unwrap_or(rest.len());
+        if rest[len..].starts_with(close)
+            && rest[..len].contains(' ')
+            && !rest[..len].ends_with(' ')
+        {
+            (range.start..range.end + len, close_reason)
+        } else {
+            // Only an opening delimiter, the URL ends at the space as usual
+            (range, reason)
+        }
+    }
+
+    /// Scan for an URL without `//` after the scheme, e.g. `urn:isbn:0451450523`.
+    fn scan_without_slashes(
+        &self,
+        s: &str,
+        separator: usize,
+    ) -> Option<(Range<usize>, TerminationReason)> {
+        // Nothing to do for the default schemes, they all need `//`
+        self.config.schemes.as_ref()?;
+
+        if let (Some(start), quote) =
+            find_scheme_start(&s[0..separator], self.config.max_scheme_len)
+        {
+            let scheme = &s[start..separator];
+            if !self.config.scheme_allowed(scheme) || self.config.scheme_requires_slashes(scheme) {
+                return None;
+            }
+            let after_separator = separator + ":".len();
+            let keep_trailing = self.config.keep_trailing(scheme);
+            let (end, reason) =
+                find_path_end(&s[after_separator..], quote, keep_trailing, &self.config)?;
+            if end == 0 {
+                return None;
+            }
+            let range = Range {
+                start,
+                end: after_separator + end,
+            };
+            return Some((range, reason));
+        }
+
+        None
+    }
+}
+
+impl Scanner for DomainNameScanner {
     fn scan(&self, s: &str, separator: usize) -> Option<Range<usize>> {
+        if separator == 0 || s.len() < MIN_URL_LENGTH {
+            return None;
+        }
+
+        let iri_parsing_enabled = self.config.iri_parsing_enabled;
+        let start = find_domain_start(&s[0..separator], &self.config).0?;
+        let s = &s[start..];
+
+        if let (Some(end), Some(last_dot)) = find_authority_end(
+            s,
+            false,
+            true,
+            false,
+            iri_parsing_enabled,
+            false,
+            None,
+            &self.config.whitespace,
+        ) {
+            let end = self.config.trim_authority_end(s, end);
+            if end <= separator - start
+                || last_dot >= end
+                || !valid_tld(&s[last_dot + 1..end], true)
+            {
+                return None;
+            }
+            // Only bare domains, not the start of a URL like `example.com/path` or
+            // `example.com:8080`
+            let rest = &s[end..];
+            let port = rest.starts_with(':') && rest[1..].starts_with(|c: char| c.is_ascii_digit());
+            if port || rest.starts_with(&['/', '?', '#'][..]) || continues_email_local_part(rest) {
+                return None;
+            }
+            return Some(Range {
+                start,
+                end: start + end,
+            });
+        }
+
+        None
+    }
+}
+
+impl Scanner for DomainScanner {
+    fn scan(&self, s: &str, i: usize) -> Option<Range<usize>> {
+        self.scan_with_reason(s, i).map(|(range, _)| range)
+    }
+
+    fn scan_with_reason(
+        &self,
+        s: &str,
+        separator: usize,
+    ) -> Option<(Range<usize>, TerminationReason)> {
         // There must be something before separator for domain, and a minimum number of characters
         if separator == 0 || s.len() < MIN_URL_LENGTH {
             return None;
         }
 
-        if let (Some(start), quote) = find_domain_start(&s[0..separator], self.iri_parsing_enabled)
-        {
+        if let (Some(start), quote) = find_domain_start(&s[0..separator], &self.config) {
             let s = &s[start..];
 
-            if let (Some(domain_end), Some(_)) =
-                find_authority_end(s, false, true, true, self.iri_parsing_enabled)
-            {
-                if let Some(end) = find_url_end(&s[domain_end..], quote, self.iri_parsing_enabled) {
+            if let (Some(domain_end), Some(_)) = find_authority_end(
+                s,
+                false,
+                true,
+                true,
+                self.config.iri_parsing_enabled,
+                false,
+                None,
+                &self.config.whitespace,
+            ) {
+                let domain_end = self.config.trim_authority_end(s, domain_end);
+                // Without a scheme there's no userinfo, so a `:` can only start a port
+                let domain_end = match s[..domain_end].find(':') {
+                    Some(colon) if is_port(&s[colon + 1..domain_end]) => domain_end,
+                    Some(colon) => colon,
+                    None => domain_end,
+                };
+                if domain_end <= separator - start {
+                    // The domain was cut before the dot, e.g. by a custom terminator
+                    return None;
+                }
+                if continues_email_local_part(&s[domain_end..]) {
+                    return None;
+                }
+                if self.config.reject_version_tokens && is_version_token(&s[..domain_end]) {
+                    return None;
+                }
+                let host = s[..domain_end].split(':').next().unwrap_or("");
+                if host.chars().count() < self.config.min_host_length {
+                    return None;
+                }
+                if let Some((end, reason)) =
+                    find_url_end(&s[domain_end..], quote, &[], &self.config)
+                {
                     let range = Range {
                         start,
                         end: start + domain_end + end,
                     };
-                    return Some(range);
+                    return Some((range, reason));
                 }
             }
         }
@@ -107,12 +713,161 @@ impl Scanner for DomainScanner {
     }
 }
 
+impl Scanner for SchemeRelativeScanner {
+    fn scan(&self, s: &str, i: usize) -> Option<Range<usize>> {
+        self.scan_with_reason(s, i).map(|(range, _)| range)
+    }
+
+    fn scan_with_reason(&self, s: &str, slash: usize) -> Option<(Range<usize>, TerminationReason)> {
+        if !s[slash..].starts_with("//") {
+            return None;
+        }
+
+        // Only allow `//` at the start of a word, e.g. in `src="//example.org"` but not in
+        // `a//example.org` or in the `//` of a URL with a scheme that we didn't find.
+        let quote = match s[..slash].chars().next_back() {
+            None => None,
+            Some(c) if QUOTES.contains(&c) => Some(c),
+            Some(c) if c.is_whitespace() => None,
+            Some('(') | Some('[') | Some('{') | Some('<') | Some('=') => None,
+            Some(_) => return None,
+        };
+
+        let after_separator = slash + "//".len();
+        let s = &s[after_separator..];
+
+        if let (Some(after_authority), last_dot) = find_authority_end(
+            s,
+            false,
+            true,
+            true,
+            self.config.iri_parsing_enabled,
+            false,
+            None,
+            &self.config.whitespace,
+        ) {
+            let after_authority = self.config.trim_authority_end(s, after_authority);
+            // Like for plain domains, require something that looks like a domain name (or an
+            // IPv6 address), otherwise things like `//foo` in comments would be found.
+            if after_authority == 0 || (last_dot.is_none() && !s.starts_with('[')) {
+                return None;
+            }
+            let authority = &s[..after_authority];
+            let host = match authority.rfind('@') {
+                Some(at) => &authority[at + 1..],
+                None => authority,
+            };
+            let host = match host.find(']') {
+                Some(close) => &host[..=close],
+                None => host_without_port(host),
+            };
+            if host.chars().count() < self.config.min_host_length {
+                return None;
+            }
+            if let Some((end, reason)) =
+                find_url_end(&s[after_authority..], quote, &[], &self.config)
+            {
+                let range = Range {
+                    start: slash,
+                    end: after_separator + after_authority + end,
+                };
+                return Some((range, reason));
+            }
+        }
+
+        None
+    }
+}
+
+impl Scanner for ScpScanner {
+    fn scan(&self, s: &str, i: usize) -> Option<Range<usize>> {
+        self.scan_with_reason(s, i).map(|(range, _)| range)
+    }
+
+    fn scan_with_reason(&self, s: &str, at: usize) -> Option<(Range<usize>, TerminationReason)> {
+        // The part before the path looks like an email address, `user@host`
+        let range = self.email_scanner.scan(s, at)?;
+        let path = s[range.end..].strip_prefix(':')?;
+        match path.chars().next() {
+            Some(c) if c.is_alphanumeric() || "/~._-".contains(c) => {}
+            _ => return None,
+        }
+        let (end, reason) = find_path_end(path, None, &[], &self.config)?;
+        if end == 0 {
+            return None;
+        }
+        let range = Range {
+            start: range.start,
+            end: range.end + ":".len() + end,
+        };
+        Some((range, reason))
+    }
+}
+
+impl Scanner for KnownHostScanner {
+    fn scan(&self, s: &str, i: usize) -> Option<Range<usize>> {
+        self.scan_with_reason(s, i).map(|(range, _)| range)
+    }
+
+    fn scan_with_reason(&self, s: &str, start: usize) -> Option<(Range<usize>, TerminationReason)> {
+        // The host must not be part of a longer host, path or email address
+        let quote = match s[..start].chars().next_back() {
+            Some(c) if QUOTES.contains(&c) => Some(c),
+            Some(c) if c.is_alphanumeric() || "-._@/:".contains(c) => return None,
+            _ => None,
+        };
+
+        let mut end = start + self.config.known_host_len(&s[start..])?;
+        if let Some(port) = s[end..].strip_prefix(':') {
+            let digits = port.bytes().take_while(u8::is_ascii_digit).count();
+            if digits > 0 && port[..digits].parse::<u16>().is_ok() {
+                end += ":".len() + digits;
+            }
+        }
+        let (url_end, reason) = find_url_end(&s[end..], quote, &[], &self.config)?;
+        let range = Range {
+            start,
+            end: end + url_end,
+        };
+        Some((range, reason))
+    }
+}
+
+/// Check whether `s` continues a host name, e.g. `.org` after `example`, but not `.` at the end
+/// of a sentence.
+fn continues_host(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_alphanumeric() || c == '-' || c == '_' => true,
+        Some('.') => matches!(chars.next(), Some(c) if c.is_alphanumeric()),
+        _ => false,
+    }
+}
+
+/// Check whether a plain domain looks like a version number such as `1.2.3.4000` rather than an
+/// IPv4 address (TLDs are already checked, so e.g. `v1.2.3` never gets here).
+fn is_version_token(host: &str) -> bool {
+    let host = match host.rfind(':') {
+        Some(colon) => &host[..colon],
+        None => host,
+    };
+    host.bytes().all(|b| b.is_ascii_digit() || b == b'.') && !is_ipv4(host)
+}
+
 /// Find start of scheme, e.g. from `https://`, start at `s` and end at `h`.
-fn find_scheme_start(s: &str) -> (Option<usize>, Option<char>) {
+///
+/// If the scheme would be longer than `max_len`, there's no scheme.
+fn find_scheme_start(s: &str, max_len: Option<usize>) -> (Option<usize>, Option<char>) {
     let mut first = None;
     let mut special = None;
     let mut quote = None;
     for (i, c) in s.char_indices().rev() {
+        if matches!(max_len, Some(max) if s.len() - i > max)
+            && matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '+' | '-' | '.')
+        {
+            // Don't look further back for very long "schemes", these are not real
+            return (None, None);
+        }
         match c {
             'a'..='z' | 'A'..='Z' => first = Some(i),
             '0'..='9' => special = Some(i),
@@ -156,9 +911,54 @@ fn find_scheme_start(s: &str) -> (Option<usize>, Option<char>) {
 /// Whether a scheme requires that authority looks like a host name (domain or IP address) or not
 /// (can contain reg-name with arbitrary allowed characters).
 ///
-/// We could make this configurable, but let's keep it simple until someone asks (hi!).
+/// This is the default when no `SchemeSet` is used, see `SchemeSet::require_host`.
 fn scheme_requires_host(scheme: &str) -> bool {
-    matches!(scheme, "https" | "http" | "ftp" | "ssh")
+    SCHEMES_REQUIRING_HOST
+        .iter()
+        .any(|s| s.eq_ignore_ascii_case(scheme))
+}
+
+/// Whether the host of `authority` has more than one label (which includes IPv4 addresses) or is
+/// an IPv6 address, e.g. `example.org` or `[::1]` but not `localhost` or `localhost.`.
+fn is_dotted_host(authority: &str) -> bool {
+    let host = match authority.rfind('@') {
+        Some(at) => &authority[at + 1..],
+        None => authority,
+    };
+    if host.starts_with('[') {
+        return true;
+    }
+    host_without_port(host).trim_end_matches('.').contains('.')
+}
+
+/// Find the end of the authority at the start of `s` if its host is an IPv4 address in one of
+/// the numeric forms, e.g. `2130706433` or `0x7f.0.0.1` (see `UrlConfig::allow_numeric_ipv4`).
+fn find_numeric_ipv4_authority_end(s: &str) -> Option<usize> {
+    let end = find_authority_end(
+        s,
+        true,
+        false,
+        true,
+        false,
+        false,
+        None,
+        &WhitespacePolicy::All,
+    )
+    .0?;
+    let authority = &s[..end];
+    let host = match authority.rfind('@') {
+        Some(at) => &authority[at + 1..],
+        None => authority,
+    };
+    parse_numeric_ipv4(host_without_port(host)).map(|_| end)
+}
+
+/// The host without the `:port` (if any).
+fn host_without_port(host: &str) -> &str {
+    match host.rfind(':') {
+        Some(colon) => &host[..colon],
+        None => host,
+    }
 }
 
 /// Find the start of a plain domain URL (no scheme), e.g. from `blog.`, start at `g` and end at `b`.
@@ -166,8 +966,9 @@ fn scheme_requires_host(scheme: &str) -> bool {
 /// - Domain is labels separated by `.`. Because we're starting at the first `.`, we only need to
 ///   handle one label.
 /// - Label can not start or end with `-`
-/// - Label can contain letters, digits, `-` or Unicode if iri_allowed flag is true
-fn find_domain_start(s: &str, iri_parsing_enabled: bool) -> (Option<usize>, Option<char>) {
+/// - Label can contain letters, digits, `-` or Unicode if iri_allowed flag is true (except for
+///   the additional brackets of `balance_pairs`)
+fn find_domain_start(s: &str, config: &UrlConfig) -> (Option<usize>, Option<char>) {
     let mut first = None;
     let mut quote = None;
 
@@ -175,11 +976,17 @@ fn find_domain_start(s: &str, iri_parsing_enabled: bool) -> (Option<usize>, Opti
         match c {
             'a'..='z' | 'A'..='Z' | '0'..='9' => first = Some(i),
             // Allow international characters but exclude Unicode whitespace
-            // (e.g., NBSP, EM SPACE, IDEOGRAPHIC SPACE)
-            '\u{80}'..=char::MAX if iri_parsing_enabled && !c.is_whitespace() => first = Some(i),
+            // (e.g., NBSP, EM SPACE, IDEOGRAPHIC SPACE), depending on the policy
+            '\u{80}'..=char::MAX
+                if config.iri_parsing_enabled
+                    && !config.whitespace.terminates(c)
+                    && !config.is_pair_bracket(c) =>
+            {
+                first = Some(i)
+            }
             // If we had something valid like `https://www.` we'd have found it with the ":"
             // scanner already. We don't want to allow `.../www.example.com` just by itself.
-            // We *could* allow `//www.example.com` (scheme-relative URLs) in the future.
+            // Scheme-relative URLs like `//www.example.com` are found by `SchemeRelativeScanner`.
             '/' => return (None, None),
             // Similar to above, if this was an email we'd have found it already.
             '@' => return (None, None),
@@ -213,23 +1020,147 @@ fn find_domain_start(s: &str, iri_parsing_enabled: bool) -> (Option<usize>, Opti
     (first, quote)
 }
 
+/// Check if `rest`, the text after a domain without scheme, continues the local part of an
+/// email address, e.g. `+tag@example.org` after `first.last`. The domain is then part of the
+/// email address and not a link of its own.
+fn continues_email_local_part(rest: &str) -> bool {
+    if rest.starts_with(&['/', '?', '#'][..]) {
+        // A path, query or fragment of the URL
+        return false;
+    }
+    for c in rest.chars() {
+        match c {
+            '@' => return true,
+            '.' => {}
+            c if is_email_local_char(c) => {}
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Check if `s` is a port number (without the `:`).
+fn is_port(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
 /// Find the end of a URL. At this point we already scanned past a valid authority. So e.g. in
 /// `https://example.com/foo` we're starting at `/` and want to end at `o`.
-fn find_url_end(s: &str, quote: Option<char>, iri_parsing_enabled: bool) -> Option<usize> {
+///
+/// The `keep_trailing` characters are not treated as trailing punctuation. Also returns why the
+/// URL ends there.
+fn find_url_end(
+    s: &str,
+    quote: Option<char>,
+    keep_trailing: &[char],
+    config: &UrlConfig,
+) -> Option<(usize, TerminationReason)> {
+    let (end, reason) = if s.starts_with(&['/', '?', '#'][..]) {
+        find_path_end(s, quote, keep_trailing, config)?
+    } else {
+        (0, termination_at(s, quote))
+    };
+    if end == 0 && config.require_path {
+        None
+    } else {
+        Some((end, reason))
+    }
+}
+
+/// Why the link at `range` of `s` ends there, from the character after it, for scanners that
+/// don't record it while scanning.
+pub(crate) fn termination_after(s: &str, range: &Range<usize>) -> TerminationReason {
+    let quote = s[..range.start]
+        .chars()
+        .next_back()
+        .filter(|c| QUOTES.contains(c));
+    termination_at(&s[range.end..], quote)
+}
+
+/// Why a link ends right before `rest` when the end was not found by scanning a path, e.g. at
+/// the end of the authority. `quote` is the quote before the link, if any.
+pub(crate) fn termination_at(rest: &str, quote: Option<char>) -> TerminationReason {
+    let c = match rest.chars().next() {
+        Some(c) => c,
+        None => return TerminationReason::EndOfInput,
+    };
+    match c {
+        _ if c.is_whitespace() => TerminationReason::Whitespace,
+        _ if Some(c) == quote => TerminationReason::Quote,
+        '\u{00}'..='\u{1F}' | '|' | '"' | '<' | '>' | '`' | '\u{7F}'..='\u{9F}' => {
+            TerminationReason::InvalidCharacter
+        }
+        ')' | ']' | '}' => TerminationReason::UnbalancedBracket,
+        '?' | '.' | ',' | ':' | ';' | '*' | '!' | '\'' | '(' | '[' | '{' => {
+            TerminationReason::TrailingPunctuation
+        }
+        _ => TerminationReason::Delimiter,
+    }
+}
+
+/// Find the end of the path (or query) of a URL, like `find_url_end` but without requiring `s`
+/// to start with `/` or `?`, e.g. for the path `owner/repo.git` of `git@example.org:owner/repo.git`.
+/// Also returns why the path ends there.
+fn find_path_end(
+    s: &str,
+    quote: Option<char>,
+    keep_trailing: &[char],
+    config: &UrlConfig,
+) -> Option<(usize, TerminationReason)> {
     let mut round = 0;
     let mut square = 0;
     let mut curly = 0;
+    // Depth of each of the additional pairs of brackets
+    let mut pairs = vec![0; config.balance_pairs.len()];
     let mut single_quote = false;
 
     let mut previous_can_be_last = true;
+    let mut previous_symbol = false;
     let mut end = Some(0);
-
-    if !s[0..].starts_with("/") && !s[0..].starts_with("?") {
-        return Some(0);
-    }
+    // Why and where the loop below stopped
+    let mut reason = TerminationReason::EndOfInput;
+    let mut stop = 0;
+    // Index up to which characters belong to an HTML entity that was already handled
+    let mut skip_until = 0;
 
     for (i, c) in s.char_indices() {
+        if i < skip_until {
+            continue;
+        }
+        stop = i;
+        let mut symbol = false;
+        let depth = round + square + curly + pairs.iter().sum::<i32>();
+        let open_pair = config.balance_pairs.iter().position(|&(open, _)| open == c);
+        let close_pair = config
+            .balance_pairs
+            .iter()
+            .position(|&(_, close)| close == c);
         let can_be_last = match c {
+            _ if config.path_terminators.contains(&c) => {
+                reason = TerminationReason::Delimiter;
+                break;
+            }
+            '%' if config.strict_percent && decode_hex_pair(&s.as_bytes()[i + 1..]).is_none() => {
+                // Not percent-encoding, e.g. in `https://example.org/50%`
+                reason = TerminationReason::Delimiter;
+                break;
+            }
+            '&' if config.decode_html_entities => match parse_entity(&s[i..]) {
+                Some((c, _)) if c.is_whitespace() || matches!(c, '"' | '<' | '>') => {
+                    // Escaped versions of characters that can't be part of an URL, see below
+                    reason = invalid_character_reason(c, quote);
+                    break;
+                }
+                Some((_, len)) => {
+                    // An escaped character such as `&amp;`, which can be part of the URL and at
+                    // the end, so don't treat the `;` as trailing punctuation.
+                    skip_until = i + len;
+                    end = Some(skip_until);
+                    previous_can_be_last = true;
+                    continue;
+                }
+                None => true,
+            },
             '\u{00}'..='\u{1F}' | ' ' | '|' | '\"' | '<' | '>' | '`' | '\u{7F}'..='\u{9F}' => {
                 // These can never be part of an URL, so stop now. See RFC 3986 and RFC 3987.
                 // Some characters are not in the above list, even they are not in "unreserved"
@@ -237,8 +1168,14 @@ fn find_url_end(s: &str, quote: Option<char>, iri_parsing_enabled: bool) -> Opti
                 //   '\\', '^', '{', '}'
                 // The reason for this is that other link detectors also allow them. Also see
                 // below, we require the braces to be balanced.
+                reason = invalid_character_reason(c, quote);
                 break;
             }
+            '?' | '.' | ',' | ':' | ';' | '*' | '!' if keep_trailing.contains(&c) => {
+                // Punctuation that is meaningful at the end for this scheme
+                true
+            }
+            '*' if config.compat == CompatMode::AutolinkJava => true,
             '?' | '.' | ',' | ':' | ';' | '*' => {
                 // These may be part of an URL but not at the end. It's not that the spec
                 // doesn't allow them, but they are frequently used in plain text as delimiters
@@ -252,47 +1189,101 @@ fn find_url_end(s: &str, quote: Option<char>, iri_parsing_enabled: bool) -> Opti
             }
             '/' => {
                 // This may be part of an URL and at the end, but not if the previous character
-                // can't be the end of an URL
-                previous_can_be_last
+                // can't be the end of an URL (unless it was a symbol, like in `/😀/`)
+                previous_can_be_last || previous_symbol
+            }
+            '(' | '[' | '{' if config.exceeds_bracket_depth(depth) => {
+                // Too deeply nested, end the URL before the bracket
+                reason = TerminationReason::Delimiter;
+                break;
+            }
+            '(' if !config.balance.round => {
+                reason = TerminationReason::Delimiter;
+                break;
             }
             '(' => {
                 round += 1;
                 false
             }
+            ')' if !config.balance.round => {
+                reason = TerminationReason::Delimiter;
+                break;
+            }
             ')' => {
                 round -= 1;
                 if round < 0 {
                     // More closing than opening brackets, stop now
+                    reason = TerminationReason::UnbalancedBracket;
                     break;
                 }
                 true
             }
+            '[' if !config.balance.square => {
+                reason = TerminationReason::Delimiter;
+                break;
+            }
             '[' => {
                 square += 1;
                 false
             }
+            ']' if !config.balance.square => {
+                reason = TerminationReason::Delimiter;
+                break;
+            }
             ']' => {
                 square -= 1;
                 if square < 0 {
                     // More closing than opening brackets, stop now
+                    reason = TerminationReason::UnbalancedBracket;
                     break;
                 }
                 true
             }
+            '{' if !config.balance.curly => {
+                reason = TerminationReason::Delimiter;
+                break;
+            }
             '{' => {
                 curly += 1;
                 false
             }
+            '}' if !config.balance.curly => {
+                reason = TerminationReason::Delimiter;
+                break;
+            }
             '}' => {
                 curly -= 1;
                 if curly < 0 {
                     // More closing than opening brackets, stop now
+                    reason = TerminationReason::UnbalancedBracket;
                     break;
                 }
                 true
             }
+            _ if open_pair.is_some() && config.exceeds_bracket_depth(depth) => {
+                reason = TerminationReason::Delimiter;
+                break;
+            }
+            _ if open_pair.is_some() => {
+                if let Some(index) = open_pair {
+                    pairs[index] += 1;
+                }
+                false
+            }
+            _ if close_pair.is_some() => {
+                if let Some(index) = close_pair {
+                    pairs[index] -= 1;
+                    if pairs[index] < 0 {
+                        // More closing than opening brackets, stop now
+                        reason = TerminationReason::UnbalancedBracket;
+                        break;
+                    }
+                }
+                true
+            }
             _ if Some(c) == quote => {
                 // Found matching quote from beginning of URL, stop now
+                reason = TerminationReason::Quote;
                 break;
             }
             '\'' => {
@@ -300,10 +1291,20 @@ fn find_url_end(s: &str, quote: Option<char>, iri_parsing_enabled: bool) -> Opti
                 // A single quote can only be the end of an URL if there's an even number
                 !single_quote
             }
-            // Exclude Unicode whitespace (e.g., NBSP, EM SPACE, IDEOGRAPHIC SPACE)
-            // Must come before IRI check so whitespace breaks regardless of IRI setting
-            _ if c.is_whitespace() => break,
-            '\u{80}'..=char::MAX if !iri_parsing_enabled => false,
+            // Exclude Unicode whitespace (e.g., NBSP, EM SPACE, IDEOGRAPHIC SPACE), depending
+            // on the policy. Must come before IRI check so whitespace breaks regardless of IRI
+            // setting
+            _ if config.whitespace.terminates(c) => {
+                reason = TerminationReason::Whitespace;
+                break;
+            }
+            '\u{80}'..=char::MAX if !config.iri_parsing_enabled => false,
+            '\u{80}'..=char::MAX if config.trim_trailing_symbols && is_symbol(c) => {
+                // Symbols such as emoji are allowed within URLs, but at the end they're more
+                // likely to be part of the surrounding text, e.g. `http://example.org👍`.
+                symbol = true;
+                false
+            }
 
             _ => true,
         };
@@ -311,7 +1312,31 @@ fn find_url_end(s: &str, quote: Option<char>, iri_parsing_enabled: bool) -> Opti
             end = Some(i + c.len_utf8());
         }
         previous_can_be_last = can_be_last;
+        previous_symbol = symbol;
     }
 
-    end
+    let stop = if reason == TerminationReason::EndOfInput {
+        s.len()
+    } else {
+        stop
+    };
+    end.map(|end| {
+        if end < stop {
+            (end, TerminationReason::TrailingPunctuation)
+        } else {
+            (end, reason)
+        }
+    })
+}
+
+/// The reason for a link to end at a character that can never be part of URLs, e.g. the `"`
+/// after a URL that starts after a `"`.
+fn invalid_character_reason(c: char, quote: Option<char>) -> TerminationReason {
+    if c.is_whitespace() {
+        TerminationReason::Whitespace
+    } else if Some(c) == quote {
+        TerminationReason::Quote
+    } else {
+        TerminationReason::InvalidCharacter
+    }
 }
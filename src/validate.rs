@@ -0,0 +1,149 @@
+//! Checking whether a whole string is a single link, as opposed to finding links in text.
+
+use std::ops::Range;
+
+use crate::email::EmailScanner;
+use crate::scanner::Scanner;
+use crate::url::{DomainScanner, UrlConfig, UrlScanner, DEFAULT_MAX_SCHEME_LEN};
+
+/// Options for `is_email`.
+#[derive(Clone, Debug)]
+pub struct EmailOptions {
+    domain_must_have_dot: bool,
+}
+
+impl EmailOptions {
+    /// Create options with the same defaults as `LinkFinder::new`.
+    pub fn new() -> EmailOptions {
+        EmailOptions {
+            domain_must_have_dot: true,
+        }
+    }
+
+    /// Require the domain parts of email addresses to have at least one dot.
+    /// Use `false` to also accept addresses such as `root@localhost`.
+    pub fn domain_must_have_dot(&mut self, value: bool) -> &mut EmailOptions {
+        self.domain_must_have_dot = value;
+        self
+    }
+}
+
+impl Default for EmailOptions {
+    fn default() -> Self {
+        EmailOptions::new()
+    }
+}
+
+/// Options for `is_url`.
+#[derive(Clone, Debug)]
+pub struct UrlOptions {
+    must_have_scheme: bool,
+    can_be_iri: bool,
+}
+
+impl UrlOptions {
+    /// Create options with the same defaults as `LinkFinder::new`.
+    pub fn new() -> UrlOptions {
+        UrlOptions {
+            must_have_scheme: true,
+            can_be_iri: true,
+        }
+    }
+
+    /// Set whether URLs must have a scheme, defaults to `true`.
+    /// Use `false` to also accept URLs like `example.org/foo`.
+    pub fn must_have_scheme(&mut self, value: bool) -> &mut UrlOptions {
+        self.must_have_scheme = value;
+        self
+    }
+
+    /// Sets whether URLs can be IRI according to RFC-3987, defaults to `true`.
+    /// Setting it to `false` means domains can contain ASCII characters only.
+    pub fn can_be_iri(&mut self, value: bool) -> &mut UrlOptions {
+        self.can_be_iri = value;
+        self
+    }
+}
+
+impl Default for UrlOptions {
+    fn default() -> Self {
+        UrlOptions::new()
+    }
+}
+
+/// Check whether the entire string is a single URL.
+///
+/// This uses the same rules as finding URLs with `LinkFinder`, but leading or trailing
+/// characters (including whitespace or trailing punctuation) are not allowed.
+///
+/// ```
+/// use linkify::{is_url, UrlOptions};
+///
+/// let mut options = UrlOptions::new();
+/// assert!(is_url("https://example.org/foo", &options));
+/// assert!(!is_url("https://example.org/foo.", &options));
+/// assert!(!is_url("example.org/foo", &options));
+///
+/// options.must_have_scheme(false);
+/// assert!(is_url("example.org/foo", &options));
+/// ```
+pub fn is_url(s: &str, options: &UrlOptions) -> bool {
+    let config = UrlConfig {
+        iri_parsing_enabled: options.can_be_iri,
+        // Like `LinkFinder`, so that long runs of scheme characters are checked quickly
+        max_scheme_len: Some(DEFAULT_MAX_SCHEME_LEN),
+        ..UrlConfig::default()
+    };
+    // A scheme can't contain `:` and a domain can't contain `:` before the first `.`, so the
+    // first trigger character is the one the URL would be found with.
+    if let Some(colon) = s.find(':') {
+        let scanner = UrlScanner {
+            config: config.clone(),
+        };
+        if is_whole(scanner.scan(s, colon), s) {
+            return true;
+        }
+    }
+    if !options.must_have_scheme {
+        if let Some(dot) = s.find('.') {
+            let scanner = DomainScanner { config };
+            if is_whole(scanner.scan(s, dot), s) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Check whether the entire string is a single email address.
+///
+/// This uses the same rules as finding emails with `LinkFinder`, but leading or trailing
+/// characters (including whitespace) are not allowed.
+///
+/// ```
+/// use linkify::{is_email, EmailOptions};
+///
+/// let options = EmailOptions::new();
+/// assert!(is_email("foo@example.com", &options));
+/// assert!(!is_email("foo@example.com ", &options));
+/// assert!(!is_email("root@localhost", &options));
+/// ```
+pub fn is_email(s: &str, options: &EmailOptions) -> bool {
+    let scanner = EmailScanner {
+        domain_must_have_dot: options.domain_must_have_dot,
+        conservative_local: false,
+        require_fqdn: false,
+        allow_ip_domain: false,
+    };
+    match s.find('@') {
+        Some(at) => is_whole(scanner.scan(s, at), s),
+        None => false,
+    }
+}
+
+fn is_whole(range: Option<Range<usize>>, s: &str) -> bool {
+    match range {
+        Some(range) => range.start == 0 && range.end == s.len(),
+        None => false,
+    }
+}
@@ -1,8 +1,8 @@
 mod common;
 
 use crate::common::assert_linked_with;
-use linkify::LinkFinder;
 use linkify::LinkKind;
+use linkify::{is_email, EmailOptions, LinkFinder, DEFAULT_EMAIL_SCHEMES};
 
 #[test]
 fn no_links() {
@@ -29,6 +29,30 @@ fn allowed_text() {
     );
 }
 
+#[test]
+fn conservative_local() {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Email]);
+    finder.email_conservative_local(true);
+    assert_linked_with(&finder, "key=val@host.com", "key=|val@host.com|");
+    assert_linked_with(
+        &finder,
+        "a.b_c%d+e-f@example.org",
+        "|a.b_c%d+e-f@example.org|",
+    );
+    assert_linked_with(
+        &finder,
+        "#!$%&'*+-/=?^_`{}|~@example.org",
+        "#!$%&'*+-/=?^_`{}|~@example.org",
+    );
+    assert_linked_with(&finder, "a?b@example.org", "a?|b@example.org|");
+    assert_linked_with(&finder, "{a}@example.org", "{a}@example.org");
+    assert_linked_with(&finder, "a/b+c@example.org", "a/|b+c@example.org|");
+
+    // Default is permissive
+    assert_linked("key=val@host.com", "|key=val@host.com|");
+}
+
 #[test]
 fn space_separation() {
     assert_linked("foo a@b.com", "foo |a@b.com|");
@@ -104,6 +128,72 @@ fn multiple_delimited_hard() {
     );
 }
 
+#[test]
+fn email_list() {
+    let finder = LinkFinder::new();
+    assert_eq!(
+        finder.email_list("a@xy.com;b@xy.com,c@xy.com"),
+        vec!["a@xy.com", "b@xy.com", "c@xy.com"]
+    );
+    assert_eq!(
+        finder.email_list(" Alice <a@x.com>, \"Bob, Jr.\" <b@y.org> ;\r\n\tc@z.net, "),
+        vec!["a@x.com", "b@y.org", "c@z.net"]
+    );
+    assert_eq!(
+        finder.email_list("<mailto:a@x.com>, see http://example.org/"),
+        vec!["a@x.com"]
+    );
+    assert!(finder.email_list("undisclosed-recipients:;").is_empty());
+}
+
+#[test]
+fn angle_brackets() {
+    let finder = LinkFinder::new();
+    let names = |input| -> Vec<_> {
+        finder
+            .links(input)
+            .map(|link| (link.as_str(), link.email_display_name()))
+            .collect()
+    };
+    assert_eq!(
+        names("\"John Doe\" <john@example.com>"),
+        vec![("john@example.com", Some("John Doe"))]
+    );
+    assert_eq!(
+        names("To: John Doe <john@example.com>, \"Doe, Jane \\\"JD\\\"\" <jane@example.com>"),
+        vec![
+            ("john@example.com", Some("John Doe")),
+            ("jane@example.com", Some("Doe, Jane \\\"JD\\\""))
+        ]
+    );
+    assert_eq!(
+        names("<a@example.com>, b@example.com, \"\" <c@example.com>"),
+        vec![
+            ("a@example.com", None),
+            ("b@example.com", None),
+            ("c@example.com", None)
+        ]
+    );
+    // The closing bracket is needed
+    assert_eq!(
+        names("John <john@example.com"),
+        vec![("john@example.com", None)]
+    );
+    // An escaped backslash before a quote doesn't escape it
+    assert_eq!(
+        names("\"a\\\\\" <a@example.com>"),
+        vec![("a@example.com", Some("a\\\\"))]
+    );
+    assert_eq!(
+        names("x\\\\\"ab\" <a@example.com>"),
+        vec![("a@example.com", Some("ab"))]
+    );
+
+    // Not for other links
+    let link = finder.links("John <https://example.org>").next().unwrap();
+    assert_eq!(link.email_display_name(), None);
+}
+
 #[test]
 fn international() {
     assert_linked("üñîçøðé@example.com", "|üñîçøðé@example.com|");
@@ -118,6 +208,35 @@ fn trigger_overlap() {
     assert_linked_with(&finder, "www@example.com", "|www@example.com|");
 }
 
+#[test]
+fn symbol_heavy_local_part() {
+    assert_linked("a.b+c-d_e@sub.example.co", "|a.b+c-d_e@sub.example.co|");
+    assert_linked(
+        "from no-reply+orders@shop.example today",
+        "from |no-reply+orders@shop.example| today",
+    );
+
+    // The start of the local part could be a domain, but the full local part is still found
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    assert_linked_with(
+        &finder,
+        "first.last+tag@mail.example.org",
+        "|first.last+tag@mail.example.org|",
+    );
+    assert_linked_with(
+        &finder,
+        "x a.b+c-d_e@sub.example.co y",
+        "x |a.b+c-d_e@sub.example.co| y",
+    );
+    assert_linked_with(
+        &finder,
+        "jos\u{e9}.nu\u{f1}ez+x@example.es",
+        "|jos\u{e9}.nu\u{f1}ez+x@example.es|",
+    );
+    assert_linked_with(&finder, "example.com/a+b@c", "|example.com/a+b@c|");
+}
+
 #[test]
 fn fuzz() {
     assert_linked("a@a.xyϸ", "|a@a.xyϸ|");
@@ -172,6 +291,115 @@ fn unicode_whitespace_in_domain() {
     assert_linked("test@exämple\u{a0}.com", "test@exämple\u{a0}.com");
 }
 
+#[test]
+fn plus_addressing() {
+    let finder = LinkFinder::new();
+    let parts = |input| {
+        let link = finder.links(input).next().unwrap();
+        (link.email_base_local(), link.email_tag())
+    };
+    assert_eq!(parts("user+tag@example.com"), (Some("user"), Some("tag")));
+    assert_eq!(parts("user+a+b@example.com"), (Some("user"), Some("a+b")));
+    assert_eq!(parts("user+@example.com"), (Some("user"), Some("")));
+    assert_eq!(parts("first.last@example.com"), (Some("first.last"), None));
+    assert_eq!(parts("https://a+b@example.com/"), (None, None));
+}
+
+#[test]
+fn require_fqdn() {
+    let mut finder = LinkFinder::new();
+    finder.email_require_fqdn(true);
+    assert_linked_with(&finder, "a@example.com", "|a@example.com|");
+    assert_linked_with(&finder, "a@mail.example.co.uk", "|a@mail.example.co.uk|");
+    assert_linked_with(&finder, "a@example.xn--p1ai", "|a@example.xn--p1ai|");
+    assert_linked_with(&finder, "a@localhost", "a@localhost");
+    assert_linked_with(&finder, "a@example.c", "a@example.c");
+    assert_linked_with(&finder, "a@example.co2", "a@example.co2");
+
+    // Applies regardless of `email_domain_must_have_dot`
+    finder.email_domain_must_have_dot(false);
+    assert_linked_with(&finder, "a@localhost", "a@localhost");
+    assert_linked_with(&finder, "a@example.com", "|a@example.com|");
+}
+
+#[test]
+fn domain_must_have_dot_without_fqdn() {
+    let mut finder = LinkFinder::new();
+    finder.email_domain_must_have_dot(false);
+    assert_linked_with(&finder, "a@localhost", "|a@localhost|");
+    assert_linked_with(&finder, "a@example.co2", "|a@example.co2|");
+
+    // A TLD that only starts with letters is only found without the FQDN requirement
+    finder.email_domain_must_have_dot(true);
+    assert_linked_with(&finder, "a@example.co2", "|a@example.co2|");
+    assert_linked_with(&finder, "a@localhost", "a@localhost");
+}
+
+#[test]
+fn ip_domain() {
+    assert_not_linked("a@192.168.0.1");
+    assert_not_linked("a@[192.168.0.1]");
+
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Email]);
+    finder.email_allow_ip_domain(true);
+    assert_linked_with(&finder, "a@[192.168.0.1]", "|a@[192.168.0.1]|");
+    assert_linked_with(&finder, "(a@[192.168.0.1])", "(|a@[192.168.0.1]|)");
+    assert_linked_with(&finder, "a@[IPv6:2001:db8::1]", "|a@[IPv6:2001:db8::1]|");
+    assert_linked_with(&finder, "a@192.168.0.1", "a@192.168.0.1");
+    assert_linked_with(&finder, "a@[192.168.0]", "a@[192.168.0]");
+    assert_linked_with(&finder, "a@[example.com]", "a@[example.com]");
+    assert_linked_with(&finder, "a@[2001:db8::1]", "a@[2001:db8::1]");
+}
+
+#[test]
+fn email_schemes() {
+    // Without the option, only the address is found
+    assert_linked("xmpp:alice@example.com", "xmpp:|alice@example.com|");
+
+    let mut finder = LinkFinder::new();
+    finder.email_schemes(DEFAULT_EMAIL_SCHEMES);
+    assert_linked_with(
+        &finder,
+        "xmpp:alice@example.com",
+        "|xmpp:alice@example.com|",
+    );
+    assert_linked_with(
+        &finder,
+        "(MAILTO:a.b@example.com)",
+        "(|MAILTO:a.b@example.com|)",
+    );
+    assert_linked_with(
+        &finder,
+        "notxmpp:alice@example.com",
+        "notxmpp:|alice@example.com|",
+    );
+    assert_linked_with(&finder, "sip:alice@example.com", "sip:|alice@example.com|");
+    assert_linked_with(&finder, "xmpp:alice", "xmpp:alice");
+    assert_linked_with(
+        &finder,
+        "xmpp: alice@example.com",
+        "xmpp: |alice@example.com|",
+    );
+
+    let link = finder.links("mailto:a+b@example.com").next().unwrap();
+    assert_eq!(link.kind(), &LinkKind::Email);
+    assert_eq!(link.scheme(), Some("mailto"));
+    assert_eq!(link.email_address(), Some("a+b@example.com"));
+    assert_eq!(link.email_base_local(), Some("a"));
+    assert_eq!(link.host(), Some("example.com"));
+
+    // Also without URLs
+    finder.kinds(&[LinkKind::Email]);
+    assert_linked_with(
+        &finder,
+        "xmpp:alice@example.com",
+        "|xmpp:alice@example.com|",
+    );
+    finder.kinds(&[LinkKind::Url]);
+    assert_linked_with(&finder, "xmpp:alice@example.com", "xmpp:alice@example.com");
+}
+
 fn assert_not_linked(s: &str) {
     let mut finder = LinkFinder::new();
     finder.kinds(&[LinkKind::Email]);
@@ -184,3 +412,37 @@ fn assert_linked(input: &str, expected: &str) {
     finder.kinds(&[LinkKind::Email]);
     assert_linked_with(&finder, input, expected);
 }
+
+#[test]
+fn finder_is_email() {
+    let mut finder = LinkFinder::new();
+    assert!(finder.is_email("foo.bar+baz@example.co.uk"));
+    assert!(!finder.is_email("foo@bar.com."));
+    assert!(!finder.is_email("foo@bar.com bar@baz.com"));
+    assert!(!finder.is_email("http://example.com"));
+    assert!(!finder.is_email("a@192.168.0.1"));
+
+    finder.email_allow_ip_domain(true);
+    assert!(finder.is_email("a@[192.168.0.1]"));
+    finder.email_conservative_local(true);
+    assert!(!finder.is_email("a!b@example.com"));
+}
+
+#[test]
+fn is_email_whole_string() {
+    let options = EmailOptions::new();
+    assert!(is_email("foo@bar.com", &options));
+    assert!(is_email("foo.bar+baz@example.co.uk", &options));
+    assert!(!is_email("foo@bar.com ", &options));
+    assert!(!is_email(" foo@bar.com", &options));
+    assert!(!is_email("foo@bar.com.", &options));
+    assert!(!is_email("foo@bar.com bar@baz.com", &options));
+    assert!(!is_email("a@b", &options));
+    assert!(!is_email("foo", &options));
+    assert!(!is_email("", &options));
+
+    let mut options = EmailOptions::new();
+    options.domain_must_have_dot(false);
+    assert!(is_email("a@b", &options));
+    assert!(is_email("root@localhost", &options));
+}
@@ -0,0 +1,722 @@
+use std::borrow::Cow;
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+
+use linkify::{
+    BalanceSet, LinkFinder, LinkKind, OverlapStrategy, ScanError, Segment, TerminationReason,
+};
+
+#[test]
+fn links_with_positions() {
+    let mut finder = LinkFinder::new();
+    finder.extract_nested_urls(true);
+    let text = "http://a.org\nfoo http://b.org/?u=http://c.org\r\n  \u{E4} http://d.org\u{2028}x@y.com\r\n\
+                \r\rhttp://e.org\n\n\u{1F600} f@g.com";
+    let positions: Vec<_> = finder
+        .links_with_positions(text)
+        .map(|(link, position)| (link.as_str(), position))
+        .collect();
+    let naive: Vec<_> = finder
+        .links(text)
+        .map(|link| (link.as_str(), link.line_col()))
+        .collect();
+    assert_eq!(positions, naive);
+    assert_eq!(positions.len(), 7);
+    assert_eq!(positions[6], ("f@g.com", (9, 3)));
+}
+
+/// A reader that returns the chunks one by one, like a socket would.
+struct ChunkReader<'a> {
+    chunks: Vec<&'a [u8]>,
+}
+
+impl<'a> Read for ChunkReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.chunks.is_empty() {
+            return Ok(0);
+        }
+        let chunk = self.chunks.remove(0);
+        if chunk.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken"));
+        }
+        buf[..chunk.len()].copy_from_slice(chunk);
+        Ok(chunk.len())
+    }
+}
+
+#[test]
+fn links_from_reader() {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Url, LinkKind::Email, LinkKind::Phone]);
+    let text = "See https://example.org/\u{E4}/x and a@b.com\ncall +1 555 123 4567 now";
+    let expected: Vec<_> = finder.links(text).map(|link| link.into_owned()).collect();
+    assert_eq!(expected.len(), 3);
+
+    // Split in the middle of the URL, the multi-byte character and the phone number
+    let bytes = text.as_bytes();
+    let reader = ChunkReader {
+        chunks: vec![&bytes[..12], &bytes[12..25], &bytes[25..58], &bytes[58..]],
+    };
+    let links: Vec<_> = finder
+        .links_from_reader(reader)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(links, expected);
+
+    // Each byte on its own
+    let reader = ChunkReader {
+        chunks: bytes.chunks(1).collect(),
+    };
+    let links: Vec<_> = finder
+        .links_from_reader(reader)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(links, expected);
+
+    let links: Vec<_> = finder.links_from_reader(io::empty()).collect();
+    assert!(links.is_empty());
+
+    // Spaces don't separate links when URLs can contain them
+    finder.url_allow_spaces_when_delimited(true);
+    let text = "see <http://example.org/a b> now";
+    let reader = ChunkReader {
+        chunks: text.as_bytes().chunks(1).collect(),
+    };
+    let links: Vec<_> = finder
+        .links_from_reader(reader)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].as_str(), "http://example.org/a b");
+
+    // JSON strings and anchors that contain whitespace are not split
+    let mut json = LinkFinder::new();
+    json.json_string_values_only(true);
+    let mut anchors = LinkFinder::new();
+    anchors.skip_existing_anchors(true);
+    let inputs = [
+        (
+            &json,
+            "{\"a b http://a.org\": \"c d http://b.org\", \"e\": \"f g http://c.org\"}",
+            2,
+        ),
+        (
+            &anchors,
+            "<a href=\"x\">e f http://a.org</a> g http://b.org",
+            1,
+        ),
+    ];
+    for &(finder, text, count) in inputs.iter() {
+        let expected: Vec<_> = finder.links(text).map(|link| link.into_owned()).collect();
+        assert_eq!(expected.len(), count);
+        let reader = ChunkReader {
+            chunks: text.as_bytes().chunks(3).collect(),
+        };
+        let links: Vec<_> = finder
+            .links_from_reader(reader)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(links, expected);
+    }
+}
+
+#[test]
+fn links_from_reader_errors() {
+    let finder = LinkFinder::new();
+    let reader = ChunkReader {
+        chunks: vec![b"http://a.org http://b.", b"", b"org"],
+    };
+    let links: Vec<_> = finder.links_from_reader(reader).collect();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].as_ref().unwrap().as_str(), "http://a.org");
+    assert!(matches!(links[1], Err(ScanError::Io(_))));
+
+    // Links before invalid UTF-8 are found, including one that ends at it
+    let reader = ChunkReader {
+        chunks: vec![b"http://a.org \xFF http://b.org"],
+    };
+    let links: Vec<_> = finder.links_from_reader(reader).collect();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].as_ref().unwrap().as_str(), "http://a.org");
+    assert!(matches!(links[1], Err(ScanError::Utf8(_))));
+    let links: Vec<_> = finder
+        .links_from_reader(&b"x http://a.org/b\xFF"[..])
+        .collect();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].as_ref().unwrap().as_str(), "http://a.org/b");
+    assert!(matches!(links[1], Err(ScanError::Utf8(_))));
+
+    // Input that ends in the middle of a character
+    let reader = ChunkReader {
+        chunks: vec![b"http://a.org \xC3"],
+    };
+    let links: Vec<_> = finder.links_from_reader(reader).collect();
+    assert_eq!(links.len(), 2);
+    assert!(matches!(links[1], Err(ScanError::Utf8(_))));
+}
+
+#[test]
+fn rescan_range() {
+    let mut finder = LinkFinder::new();
+    finder.extract_nested_urls(true);
+    let text = "a https://a.org/?u=https://b.org c@d.com\thttps://e.org/\n+1 555 123 4567";
+    let rescanned = |edited| -> Vec<_> {
+        finder
+            .rescan_range(text, edited)
+            .map(|link| {
+                let scheme = link.scheme().map(str::to_string);
+                (link.start(), link.end(), scheme, link.line_col())
+            })
+            .collect()
+    };
+    let all: Vec<_> = finder
+        .links(text)
+        .map(|link| {
+            let scheme = link.scheme().map(str::to_string);
+            (link.start(), link.end(), scheme, link.line_col())
+        })
+        .collect();
+
+    assert_eq!(rescanned(0..text.len()), all);
+    assert_eq!(rescanned(5..6), all[..2].to_vec());
+    assert_eq!(rescanned(35..35), all[2..3].to_vec());
+    assert_eq!(rescanned(0..1), vec![]);
+    assert_eq!(rescanned(1..2), all[..2].to_vec());
+    assert_eq!(rescanned(38..44), all[2..4].to_vec());
+
+    // Phone numbers contain spaces, so the window is extended to the line
+    finder.kinds(&[LinkKind::Phone]);
+    let phone = finder.rescan_range(text, 67..68).next().unwrap();
+    assert_eq!(phone.as_str(), "+1 555 123 4567");
+    assert_eq!(phone.line_col(), (2, 1));
+}
+
+#[test]
+fn line_col() {
+    let finder = LinkFinder::new();
+    let text = "http://a.org\nfoo http://b.org\r\n  ä http://c.org\u{2028}x@y.com\rhttp://d.org";
+    let positions: Vec<_> = finder.links(text).map(|l| l.line_col()).collect();
+    assert_eq!(positions, vec![(1, 1), (2, 5), (3, 5), (4, 1), (5, 1)]);
+}
+
+#[test]
+fn context() {
+    let finder = LinkFinder::new();
+    let text = "Look at this: http://example.org/ it's great";
+    let link = finder.links(text).next().unwrap();
+    assert_eq!(link.context(0), "http://example.org/");
+    assert_eq!(link.context(3), "s: http://example.org/ it");
+    assert_eq!(link.context(100), text);
+
+    // Multi-byte characters are counted as one
+    let text = "äöü http://üñîçøðé.com/ä 😀x";
+    let link = finder.links(text).next().unwrap();
+    assert_eq!(link.context(2), "ü http://üñîçøðé.com/ä 😀");
+    assert_eq!(link.context(4), "äöü http://üñîçøðé.com/ä 😀x");
+}
+
+#[test]
+fn snippet() {
+    let finder = LinkFinder::new();
+    let text = "Look at this: http://example.org/ it's great";
+    let link = finder.links(text).next().unwrap();
+    assert_eq!(link.snippet(0), ("http://example.org/".to_string(), 0..19));
+    assert_eq!(link.snippet(3), ("http://example.org/".to_string(), 0..19));
+    assert_eq!(
+        link.snippet(6),
+        ("this: http://example.org/ it's".to_string(), 6..25)
+    );
+    assert_eq!(link.snippet(100), (text.to_string(), 14..33));
+
+    // A single word is cut off at a character boundary
+    let text = "äöü http://üñîçøðé.com/ä 😀x";
+    let link = finder.links(text).next().unwrap();
+    let (snippet, range) = link.snippet(2);
+    assert_eq!(snippet, "http://üñîçøðé.com/ä");
+    assert_eq!(&snippet[range], link.as_str());
+    let text = "(äöühttp://üñîçøðé.com/ä)😀x";
+    let link = finder.links(text).next().unwrap();
+    let (snippet, range) = link.snippet(2);
+    assert_eq!(snippet, "öühttp://üñîçøðé.com/ä)😀");
+    assert_eq!(&snippet[range], link.as_str());
+}
+
+#[test]
+fn link_frequencies() {
+    let mut finder = LinkFinder::new();
+    finder.url_strip_zero_width(true);
+    let input =
+        "Buy at https://spam.example/ HTTPS://Spam.Example/ or https://spam.example/\u{200B}! \
+                 Mail a@b.com, a@B.com, A@b.com or visit https://ok.example/ \
+                 https://bit.ly/AbC https://bit.ly/abc ftp://User@FTP.example/Pub";
+    let frequencies = finder.link_frequencies(input);
+    let mut frequencies: Vec<_> = frequencies.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+    frequencies.sort();
+    assert_eq!(
+        frequencies,
+        vec![
+            ("A@b.com", 1),
+            ("a@b.com", 2),
+            ("ftp://User@ftp.example/Pub", 1),
+            ("https://bit.ly/AbC", 1),
+            ("https://bit.ly/abc", 1),
+            ("https://ok.example/", 1),
+            ("https://spam.example/", 3)
+        ]
+    );
+
+    assert!(finder.link_frequencies("no links").is_empty());
+}
+
+#[test]
+fn reset() {
+    let input = "http://a.org/x. example.com b@c.com #tag +14155552671 ｈｔｔｐ：／／ｄ．ｏｒｇ ftp://e.org";
+    let found = |finder: &LinkFinder| -> Vec<(String, LinkKind)> {
+        finder
+            .links(input)
+            .map(|link| (link.as_str().to_string(), *link.kind()))
+            .collect()
+    };
+
+    let mut finder = LinkFinder::new();
+    finder
+        .url_must_have_scheme(false)
+        .normalize_fullwidth(true)
+        .url_blocked_hosts(&["e.org"])
+        .scheme_keep_trailing(&[("http", &['.'])])
+        .kinds(&[LinkKind::Url, LinkKind::Hashtag, LinkKind::Phone]);
+    assert_ne!(found(&finder), found(&LinkFinder::new()));
+
+    finder.reset();
+    assert_eq!(found(&finder), found(&LinkFinder::new()));
+    assert_eq!(format!("{:?}", finder), format!("{:?}", LinkFinder::new()));
+}
+
+#[test]
+fn links_cow() {
+    let finder = LinkFinder::new();
+    let input = "See http://a.org/ or b@c.com";
+
+    let borrowed = Cow::Borrowed(input);
+    let links: Vec<_> = finder
+        .links_cow(&borrowed)
+        .map(|link| (link.start(), link.as_str()))
+        .collect();
+    assert_eq!(links, vec![(4, "http://a.org/"), (21, "b@c.com")]);
+
+    let owned: Cow<str> = Cow::Owned(input.replace("See", "Visit"));
+    let links: Vec<_> = finder
+        .links_cow(&owned)
+        .map(|link| (link.start(), link.as_str()))
+        .collect();
+    assert_eq!(links, vec![(6, "http://a.org/"), (23, "b@c.com")]);
+    assert_eq!(&owned[6..19], "http://a.org/");
+}
+
+#[test]
+fn trailing_trimmed() {
+    let finder = LinkFinder::new();
+    let trimmed = |input| -> Vec<_> {
+        finder
+            .links(input)
+            .map(|link| link.trailing_trimmed())
+            .collect()
+    };
+    assert_eq!(trimmed("http://x.org/."), vec!["."]);
+    assert_eq!(trimmed("(see http://x.org/a_(b))."), vec![")."]);
+    assert_eq!(
+        trimmed("http://x.org/?! and a@b.com,\nc@d.com"),
+        vec!["?!", ",", ""]
+    );
+    assert_eq!(trimmed("http://x.org/a!\u{A0}x"), vec!["!"]);
+}
+
+#[test]
+fn sentence() {
+    let finder = LinkFinder::new();
+    let sentences = |input| -> Vec<_> { finder.links(input).map(|link| link.sentence()).collect() };
+    assert_eq!(
+        sentences("First. See http://x.org/a.b?c=1 for more. Then a@b.com works! Ok"),
+        vec!["See http://x.org/a.b?c=1 for more.", "Then a@b.com works!"]
+    );
+    // The link is at the end of the sentence, with the punctuation after it
+    assert_eq!(
+        sentences("Is it http://x.org/? Yes. Go to http://y.org/."),
+        vec!["Is it http://x.org/?", "Go to http://y.org/."]
+    );
+    // Line breaks end sentences, and abbreviations like `e.g.` only end them before whitespace
+    assert_eq!(
+        sentences("Title\n  e.g.http://x.org/ v1.2 and more\r\nNext line"),
+        vec!["e.g.http://x.org/ v1.2 and more"]
+    );
+    assert_eq!(sentences("http://x.org/"), vec!["http://x.org/"]);
+}
+
+#[test]
+fn termination_reason() {
+    let finder = LinkFinder::new();
+    let reason = |input| finder.links(input).next().unwrap().termination_reason();
+    assert_eq!(reason("http://example.org/"), TerminationReason::EndOfInput);
+    assert_eq!(
+        reason("http://example.org/ a"),
+        TerminationReason::Whitespace
+    );
+    assert_eq!(
+        reason("http://example.org/\u{a0}a"),
+        TerminationReason::Whitespace
+    );
+    assert_eq!(
+        reason("http://example.org/<p>"),
+        TerminationReason::InvalidCharacter
+    );
+    assert_eq!(
+        reason("(http://example.org/)"),
+        TerminationReason::UnbalancedBracket
+    );
+    assert_eq!(reason("'http://example.org/'"), TerminationReason::Quote);
+    assert_eq!(reason("\"http://example.org/\""), TerminationReason::Quote);
+    assert_eq!(
+        reason("http://example.org/."),
+        TerminationReason::TrailingPunctuation
+    );
+    assert_eq!(
+        reason("http://example.org/'."),
+        TerminationReason::TrailingPunctuation
+    );
+    assert_eq!(reason("foo@example.org/"), TerminationReason::Delimiter);
+
+    // Recorded by the scanner, not derived from the character after the link
+    let mut finder = LinkFinder::new();
+    finder.url_balance_chars(BalanceSet::all().round(false));
+    let link = finder.links("http://example.org/a)").next().unwrap();
+    assert_eq!(link.termination_reason(), TerminationReason::Delimiter);
+
+    let mut finder = LinkFinder::new();
+    finder.url_decode_before_scan(true);
+    let link = finder
+        .links("http%3A%2F%2Fexample.org%2Fa%20b")
+        .next()
+        .unwrap();
+    assert_eq!(link.as_str(), "http%3A%2F%2Fexample.org%2Fa");
+    assert_eq!(link.termination_reason(), TerminationReason::Whitespace);
+
+    let mut finder = LinkFinder::new();
+    finder.url_allow_spaces_when_delimited(true);
+    let link = finder.links("<http://example.org/a b> c").next().unwrap();
+    assert_eq!(
+        link.termination_reason(),
+        TerminationReason::InvalidCharacter
+    );
+
+    // The end of a rescanned range is not the end of the input
+    let text = "see http://example.org/ now";
+    let link = finder.rescan_range(text, 5..6).next().unwrap();
+    assert_eq!(link.termination_reason(), TerminationReason::Whitespace);
+}
+
+#[test]
+fn scan_into() {
+    let finder = LinkFinder::new();
+    let mut links = Vec::new();
+
+    finder.scan_into("http://a.org and b@c.org", &mut links);
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[1].as_str(), "b@c.org");
+    let capacity = links.capacity();
+
+    finder.scan_into("only http://d.org", &mut links);
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].as_str(), "http://d.org");
+    assert_eq!(links.capacity(), capacity);
+
+    finder.scan_into("nothing", &mut links);
+    assert!(links.is_empty());
+}
+
+#[test]
+fn links_limit() {
+    let finder = LinkFinder::new();
+    let text = "http://a.org http://b.org http://c.org http://d.org";
+    let links: Vec<_> = finder.links_limit(text, 3).map(|l| l.as_str()).collect();
+    assert_eq!(links, vec!["http://a.org", "http://b.org", "http://c.org"]);
+    assert_eq!(finder.links_limit(text, 0).count(), 0);
+    assert_eq!(finder.links_limit(text, 10).count(), 4);
+
+    assert_stops_early(|text| assert_eq!(finder.links_limit(text, 1).count(), 1));
+}
+
+#[test]
+fn segments() {
+    let finder = LinkFinder::new();
+    let text = "Mail a@b.com or see http://a.org/?u=http://b.org!";
+    let segments = finder.segments(text);
+    let texts: Vec<_> = segments.iter().map(|s| s.as_str()).collect();
+    assert_eq!(
+        texts,
+        vec![
+            "Mail ",
+            "a@b.com",
+            " or see ",
+            "http://a.org/?u=http://b.org",
+            "!"
+        ]
+    );
+    assert_eq!(texts.concat(), text);
+    match &segments[1] {
+        Segment::Link(link) => {
+            assert_eq!(link.kind(), &LinkKind::Email);
+            assert_eq!((link.start(), link.end()), (5, 12));
+        }
+        Segment::Text(_) => panic!("expected a link"),
+    }
+
+    // Nested links are not included
+    let mut finder = LinkFinder::new();
+    finder.extract_nested_urls(true);
+    let nested: Vec<_> = finder
+        .segments(text)
+        .iter()
+        .map(|s| s.as_str().to_string())
+        .collect();
+    assert_eq!(nested, texts);
+
+    assert!(finder.segments("").is_empty());
+    assert_eq!(
+        finder.segments("no links"),
+        vec![Segment::Text("no links".to_string())]
+    );
+}
+
+#[test]
+fn as_str_lower() {
+    let finder = LinkFinder::new();
+    let text = "İ http://İ.example.org/A a@B.com http://example.org/x";
+    let links: Vec<_> = finder.links(text).collect();
+    let lower: Vec<_> = links.iter().map(|link| link.as_str_lower()).collect();
+    assert_eq!(
+        lower,
+        vec![
+            "http://i\u{307}.example.org/a",
+            "a@b.com",
+            "http://example.org/x"
+        ]
+    );
+    assert!(matches!(lower[2], Cow::Borrowed(_)));
+
+    // Offsets still refer to the input, even though the lowercase `İ` is longer
+    assert_eq!(&text[links[1].start()..links[1].end()], "a@B.com");
+    assert_eq!(links[0].as_str(), "http://İ.example.org/A");
+}
+
+#[test]
+fn links_with_context() {
+    let finder = LinkFinder::new();
+    let text = "ä(http://a.org/ü) b@c.com, “http://d.org/”\u{A0}x";
+    let context: Vec<_> = finder
+        .links_with_context(text)
+        .map(|(before, link, after)| (before, link.as_str(), after))
+        .collect();
+    assert_eq!(
+        context,
+        vec![
+            (Some('('), "http://a.org/ü", Some(')')),
+            (Some(' '), "b@c.com", Some(',')),
+            (Some('“'), "http://d.org/”", Some('\u{A0}')),
+        ]
+    );
+
+    let context: Vec<_> = finder
+        .links_with_context("http://a.org")
+        .map(|(before, _, after)| (before, after))
+        .collect();
+    assert_eq!(context, vec![(None, None)]);
+}
+
+#[test]
+fn links_with_prefix() {
+    let mut finder = LinkFinder::new();
+    finder.extract_nested_urls(true);
+    let text = "https://a.com/?u=https://b.com/ and c@d.com, https://e.com/ end";
+    let pairs: Vec<_> = finder
+        .links_with_prefix(text)
+        .map(|(before, link)| (before, link.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("", "https://a.com/?u=https://b.com/"),
+            (" and ", "c@d.com"),
+            (", ", "https://e.com/"),
+        ]
+    );
+    let rebuilt: String = pairs.iter().flat_map(|(a, b)| vec![*a, *b]).collect();
+    assert_eq!(rebuilt + " end", text);
+
+    assert_eq!(finder.links_with_prefix("no links").count(), 0);
+}
+
+#[test]
+fn has_link() {
+    let mut finder = LinkFinder::new();
+    assert!(finder.has_link("a@example.com"));
+    assert!(finder.has_link("foo http://example.org bar"));
+    assert!(!finder.has_link(""));
+    assert!(!finder.has_link("http:// foo@bar example.org"));
+
+    finder.kinds(&[LinkKind::Url]);
+    assert!(!finder.has_link("a@example.com"));
+
+    let mut finder = LinkFinder::new();
+    assert_stops_early(|text| assert!(finder.has_link(text)));
+    finder.overlap_strategy(OverlapStrategy::LongestCover);
+    assert!(finder.has_link("see http://example.org"));
+    assert!(!finder.has_link("nothing"));
+    assert_stops_early(|text| assert!(finder.has_link(text)));
+}
+
+#[test]
+fn link_at() {
+    let finder = LinkFinder::new();
+    let text = "\u{E9} https://example.org/\u{E9} and a@example.com";
+    let link_at = |offset| finder.link_at(text, offset).map(|link| link.as_str());
+
+    assert_eq!(link_at(0), None);
+    assert_eq!(link_at(3), Some("https://example.org/\u{E9}"));
+    assert_eq!(link_at(22), Some("https://example.org/\u{E9}"));
+    // In the middle of the last character of the URL
+    assert_eq!(link_at(24), Some("https://example.org/\u{E9}"));
+    assert_eq!(link_at(25), None);
+    assert_eq!(link_at(30), Some("a@example.com"));
+    assert_eq!(link_at(text.len()), None);
+    assert_eq!(link_at(1000), None);
+
+    // The outer link is returned for nested links
+    let mut finder = LinkFinder::new();
+    finder.extract_nested_urls(true);
+    let link = finder
+        .link_at("https://a.com/?u=https://b.com/", 20)
+        .unwrap();
+    assert_eq!(link.as_str(), "https://a.com/?u=https://b.com/");
+
+    let finder = LinkFinder::new();
+    assert_stops_early(|text| assert!(finder.link_at(text, 5).is_some()));
+}
+
+#[test]
+fn links_many() {
+    let finder = LinkFinder::new();
+    let texts = ["https://a.com and https://b.com", "", "mail c@example.com"];
+    let links: Vec<_> = finder
+        .links_many(&texts)
+        .map(|(index, link)| (index, link.as_str(), link.start()))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            (0, "https://a.com", 0),
+            (0, "https://b.com", 18),
+            (2, "c@example.com", 5)
+        ]
+    );
+}
+
+#[test]
+fn double_ended() {
+    let mut finder = LinkFinder::new();
+    finder.extract_nested_urls(true);
+    let text = "https://a.com/?u=https://b.com/ c@example.com x https://d.com";
+
+    let forward: Vec<_> = finder.links(text).map(|link| link.as_str()).collect();
+    let mut backward: Vec<_> = finder.links(text).rev().map(|link| link.as_str()).collect();
+    backward.reverse();
+    assert_eq!(forward, backward);
+    assert_eq!(forward.len(), 4);
+
+    assert_eq!(
+        finder.links(text).next_back().unwrap().as_str(),
+        "https://d.com"
+    );
+
+    // Mixing both directions
+    let mut links = finder.links(text);
+    assert_eq!(links.next().unwrap().as_str(), forward[0]);
+    assert_eq!(links.next_back().unwrap().as_str(), forward[3]);
+    assert_eq!(links.next().unwrap().as_str(), forward[1]);
+    assert_eq!(links.next_back().unwrap().as_str(), forward[2]);
+    assert!(links.next().is_none());
+    assert!(links.next_back().is_none());
+}
+
+#[test]
+fn overlap_strategy() {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Url, LinkKind::Hashtag, LinkKind::Phone]);
+    finder.url_must_have_scheme(false);
+    let text =
+        "#tag.example.org/a and +14155552671.example.com, #tag and https://a.com/?u=https://b.com/";
+
+    let links: Vec<_> = finder.links(text).map(|link| link.as_str()).collect();
+    assert_eq!(
+        links,
+        vec![
+            "#tag",
+            "+14155552671",
+            "#tag",
+            "https://a.com/?u=https://b.com/"
+        ]
+    );
+
+    finder.overlap_strategy(OverlapStrategy::LongestCover);
+    finder.extract_nested_urls(true);
+    let links: Vec<_> = finder.links(text).map(|link| link.as_str()).collect();
+    assert_eq!(
+        links,
+        vec![
+            "tag.example.org/a",
+            "14155552671.example.com",
+            "#tag",
+            "https://a.com/?u=https://b.com/",
+            "https://b.com/"
+        ]
+    );
+    let mut backward: Vec<_> = finder.links(text).rev().map(|link| link.as_str()).collect();
+    backward.reverse();
+    assert_eq!(backward, links);
+
+    // The same links are found when they don't overlap
+    let text = "#a, +14155552671 and b.com";
+    let first_start: Vec<_> = LinkFinder::new()
+        .kinds(&[LinkKind::Url, LinkKind::Hashtag, LinkKind::Phone])
+        .url_must_have_scheme(false)
+        .links(text)
+        .map(|link| link.as_str())
+        .collect();
+    let longest_cover: Vec<_> = finder.links(text).map(|link| link.as_str()).collect();
+    assert_eq!(first_start, vec!["#a", "+14155552671", "b.com"]);
+    assert_eq!(longest_cover, first_start);
+
+    // By default, only the input up to the next link is scanned
+    let finder = LinkFinder::new();
+    assert_stops_early(|text| {
+        finder.links(text).next();
+    });
+}
+
+/// Assert that `scan` doesn't scan all of an input with a link at the start, by comparing how long
+/// it takes with finding all the links of the input.
+fn assert_stops_early(scan: impl Fn(&str)) {
+    let text = format!("https://example.org {}", "a@b ".repeat(500_000));
+    let time = |f: &dyn Fn()| -> Duration {
+        let start = Instant::now();
+        f();
+        start.elapsed()
+    };
+    let all = time(&|| assert_eq!(LinkFinder::new().links(&text).count(), 1));
+    let early = time(&|| scan(&text));
+    assert!(
+        early * 10 < all,
+        "took {:?}, all links took {:?}",
+        early,
+        all
+    );
+}
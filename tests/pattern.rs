@@ -0,0 +1,93 @@
+mod common;
+
+use crate::common::assert_linked_with;
+use linkify::{LinkFinder, LinkKind, PatternSuffix};
+
+#[test]
+fn no_patterns_by_default() {
+    let finder = LinkFinder::new();
+    assert_linked_with(&finder, "Fixed in PROJ-123.", "Fixed in PROJ-123.");
+}
+
+#[test]
+fn pattern_digits() {
+    let finder = jira_finder();
+    assert_linked_with(&finder, "PROJ-123", "|PROJ-123|");
+    assert_linked_with(&finder, "Fixed in PROJ-123.", "Fixed in |PROJ-123|.");
+    assert_linked_with(&finder, "(PROJ-1, PROJ-22)", "(|PROJ-1|, |PROJ-22|)");
+    assert_linked_with(&finder, "PROJ-", "PROJ-");
+    assert_linked_with(&finder, "PROJ-abc", "PROJ-abc");
+}
+
+#[test]
+fn pattern_must_be_whole_word() {
+    let finder = jira_finder();
+    assert_linked_with(&finder, "XPROJ-123", "XPROJ-123");
+    assert_linked_with(&finder, "PROJ-123abc", "PROJ-123abc");
+    assert_linked_with(&finder, "PROJ-123-4", "PROJ-123-4");
+    assert_linked_with(&finder, "PROJ-123@example.org", "|PROJ-123@example.org|");
+}
+
+#[test]
+fn pattern_inside_url() {
+    let finder = jira_finder();
+    assert_linked_with(
+        &finder,
+        "https://example.org/PROJ-123 PROJ-4",
+        "|https://example.org/PROJ-123| |PROJ-4|",
+    );
+}
+
+#[test]
+fn pattern_alphanumeric() {
+    let mut finder = LinkFinder::new();
+    finder.add_pattern(
+        "commit:",
+        PatternSuffix::Alphanumeric,
+        "https://git.example.org/commit/{}",
+    );
+    let links: Vec<_> = finder.links("See commit:a1b2c3 and commit:").collect();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].as_str(), "commit:a1b2c3");
+    assert_eq!(links[0].kind(), &LinkKind::Pattern);
+    assert_eq!(
+        links[0].target().unwrap(),
+        "https://git.example.org/commit/commit:a1b2c3"
+    );
+}
+
+#[test]
+fn pattern_target() {
+    let finder = jira_finder();
+    let links: Vec<_> = finder.links("PROJ-7 http://example.org").collect();
+    assert_eq!(
+        links[0].target(),
+        Some("https://jira.example.com/browse/PROJ-7".to_string())
+    );
+    assert_eq!(links[1].kind(), &LinkKind::Url);
+    assert_eq!(links[1].target(), None);
+}
+
+#[test]
+fn pattern_kind_can_be_disabled() {
+    let mut finder = jira_finder();
+    finder.disable_kind(LinkKind::Pattern);
+    assert_linked_with(&finder, "PROJ-123", "PROJ-123");
+
+    finder.kinds(&[LinkKind::Pattern]);
+    assert_linked_with(
+        &finder,
+        "PROJ-1 http://example.org",
+        "|PROJ-1| http://example.org",
+    );
+}
+
+fn jira_finder() -> LinkFinder {
+    let mut finder = LinkFinder::new();
+    finder.add_pattern(
+        "PROJ-",
+        PatternSuffix::Digits,
+        "https://jira.example.com/browse/{}",
+    );
+    finder
+}
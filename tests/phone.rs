@@ -0,0 +1,112 @@
+mod common;
+
+use crate::common::assert_linked_with;
+use linkify::{LinkFinder, LinkKind, PhoneFormat};
+
+#[test]
+fn not_found_by_default() {
+    let finder = LinkFinder::new();
+    assert_linked_with(&finder, "+14155552671", "+14155552671");
+}
+
+#[test]
+fn e164() {
+    let finder = finder(&[PhoneFormat::E164]);
+    assert_linked_with(&finder, "+14155552671", "|+14155552671|");
+    assert_linked_with(&finder, "Call +442079460958.", "Call |+442079460958|.");
+    assert_linked_with(&finder, "+1234567", "+1234567");
+    assert_linked_with(&finder, "+1234567890123456", "+1234567890123456");
+    assert_linked_with(&finder, "+04155552671", "+04155552671");
+    assert_linked_with(&finder, "(415) 555-2671", "(415) 555-2671");
+    assert_linked_with(&finder, "+1 415 555 2671", "+1 415 555 2671");
+    assert_linked_with(&finder, "a+14155552671", "a+14155552671");
+}
+
+#[test]
+fn nanp() {
+    let finder = finder(&[PhoneFormat::Nanp]);
+    assert_linked_with(&finder, "(415) 555-2671", "|(415) 555-2671|");
+    assert_linked_with(&finder, "415-555-2671", "|415-555-2671|");
+    assert_linked_with(&finder, "415.555.2671", "|415.555.2671|");
+    assert_linked_with(&finder, "call 415 555 2671!", "call |415 555 2671|!");
+    assert_linked_with(&finder, "+1 415-555-2671", "|+1 415-555-2671|");
+    assert_linked_with(&finder, "1-415-555-2671", "|1-415-555-2671|");
+    assert_linked_with(&finder, "4155552671", "4155552671");
+    assert_linked_with(&finder, "115-555-2671", "115-555-2671");
+    assert_linked_with(&finder, "415-155-2671", "415-155-2671");
+    assert_linked_with(&finder, "415-555-26712", "415-555-26712");
+    assert_linked_with(&finder, "415-555-2671-2", "415-555-2671-2");
+    assert_linked_with(&finder, "2.415-555-2671", "2.415-555-2671");
+    assert_linked_with(&finder, "+14155552671", "+14155552671");
+}
+
+#[test]
+fn international() {
+    let finder = finder(&[PhoneFormat::International]);
+    assert_linked_with(&finder, "+44 20 7946 0958", "|+44 20 7946 0958|");
+    assert_linked_with(&finder, "+49-30-1234567", "|+49-30-1234567|");
+    assert_linked_with(&finder, "+14155552671", "|+14155552671|");
+    assert_linked_with(&finder, "+44 20 7946 0958 or", "|+44 20 7946 0958| or");
+    assert_linked_with(&finder, "+44  20 7946 0958", "+44  20 7946 0958");
+    assert_linked_with(&finder, "+1 234", "+1 234");
+}
+
+#[test]
+fn international_parentheses() {
+    let finder = finder(&[PhoneFormat::International]);
+    assert_linked_with(&finder, "+1 (555) 123-4567", "|+1 (555) 123-4567|");
+    assert_linked_with(&finder, "+44 (20) 7946 0958", "|+44 (20) 7946 0958|");
+    assert_linked_with(&finder, "+(1) 555 123 4567", "+(1) 555 123 4567");
+    assert_linked_with(&finder, "+1 (555 123-4567", "+1 (555 123-4567");
+}
+
+#[test]
+fn conservative() {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Phone, LinkKind::Url, LinkKind::Email]);
+    finder.phone_conservative(true);
+    assert_linked_with(
+        &finder,
+        "Call me at +1 (555) 123-4567 tomorrow.",
+        "Call me at |+1 (555) 123-4567| tomorrow.",
+    );
+    assert_linked_with(
+        &finder,
+        "The office is at (415) 555-2671, the fax at +44 20 7946 0958.",
+        "The office is at |(415) 555-2671|, the fax at |+44 20 7946 0958|.",
+    );
+    assert_linked_with(
+        &finder,
+        "Invoice 415-555-2671 and order 415.555.2671 are paid",
+        "Invoice 415-555-2671 and order 415.555.2671 are paid",
+    );
+    assert_linked_with(
+        &finder,
+        "See http://example.org/+15551234567 or mail a+15551234567@example.com",
+        "See |http://example.org/+15551234567| or mail |a+15551234567@example.com|",
+    );
+
+    // Not found unless enabled in `kinds`
+    finder.kinds(&[LinkKind::Url]);
+    assert_linked_with(&finder, "Call +1 (555) 123-4567", "Call +1 (555) 123-4567");
+}
+
+#[test]
+fn all_formats_by_default() {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Phone, LinkKind::Url]);
+    assert_linked_with(
+        &finder,
+        "+14155552671, (415) 555-2671, +44 20 7946 0958 and http://example.org/4155552671",
+        "|+14155552671|, |(415) 555-2671|, |+44 20 7946 0958| and |http://example.org/4155552671|",
+    );
+    let link = finder.links("+14155552671").next().unwrap();
+    assert_eq!(link.kind(), &LinkKind::Phone);
+}
+
+fn finder(formats: &[PhoneFormat]) -> LinkFinder {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Phone]);
+    finder.phone_formats(formats);
+    finder
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use crate::common::assert_linked_with;
+use linkify::{LinkFinder, LinkKind};
+
+#[test]
+fn not_found_by_default() {
+    let finder = LinkFinder::new();
+    assert_linked_with(&finder, "#rustlang @user", "#rustlang @user");
+}
+
+#[test]
+fn hashtag() {
+    let finder = finder(&[LinkKind::Hashtag]);
+    assert_linked_with(&finder, "#rustlang", "|#rustlang|");
+    assert_linked_with(&finder, "I love #rust_lang!", "I love |#rust_lang|!");
+    assert_linked_with(&finder, "#a #b", "|#a| |#b|");
+    assert_linked_with(&finder, "#2024 #rust2024", "#2024 |#rust2024|");
+    assert_linked_with(&finder, "#ünïcode", "|#ünïcode|");
+    assert_linked_with(&finder, "#", "#");
+    assert_linked_with(&finder, "a#b", "a#b");
+    assert_linked_with(&finder, "&#39;", "&#39;");
+    assert_linked_with(&finder, "&#x27;", "&#x27;");
+}
+
+#[test]
+fn mention() {
+    let finder = finder(&[LinkKind::Mention]);
+    assert_linked_with(&finder, "@user", "|@user|");
+    assert_linked_with(&finder, "thanks @some_user.", "thanks |@some_user|.");
+    assert_linked_with(&finder, "(@a, @b)", "(|@a|, |@b|)");
+    assert_linked_with(&finder, "@", "@");
+    assert_linked_with(&finder, "@user@example.org", "@user@example.org");
+    // Not an email address either, because emails aren't enabled
+    assert_linked_with(&finder, "foo@example.org", "foo@example.org");
+}
+
+#[test]
+fn mention_and_email() {
+    let finder = finder(&[LinkKind::Mention, LinkKind::Email]);
+    assert_linked_with(
+        &finder,
+        "@user, mail foo@example.org",
+        "|@user|, mail |foo@example.org|",
+    );
+    let kinds: Vec<_> = finder
+        .links("foo@example.org @bar")
+        .map(|link| *link.kind())
+        .collect();
+    assert_eq!(kinds, vec![LinkKind::Email, LinkKind::Mention]);
+}
+
+#[test]
+fn hashtag_in_url() {
+    let finder = finder(&[LinkKind::Hashtag, LinkKind::Url]);
+    assert_linked_with(
+        &finder,
+        "https://example.org/#top #rust",
+        "|https://example.org/#top| |#rust|",
+    );
+}
+
+#[test]
+fn name() {
+    let finder = finder(&[LinkKind::Hashtag, LinkKind::Mention, LinkKind::Url]);
+    let names: Vec<_> = finder
+        .links("#rustlang @user https://example.org")
+        .map(|link| link.name())
+        .collect();
+    assert_eq!(names, vec![Some("rustlang"), Some("user"), None]);
+}
+
+fn finder(kinds: &[LinkKind]) -> LinkFinder {
+    let mut finder = LinkFinder::new();
+    finder.kinds(kinds);
+    finder
+}
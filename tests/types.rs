@@ -1,6 +1,12 @@
+use std::collections::HashSet;
+
+use std::error::Error;
+use std::io;
+
 use linkify::Link;
 use linkify::LinkFinder;
 use linkify::LinkKind;
+use linkify::ScanError;
 
 #[test]
 fn send_and_sync() {
@@ -24,6 +30,88 @@ fn equality() {
     assert_eq!(link.kind(), &LinkKind::Url);
 }
 
+#[test]
+fn match_kind() {
+    // `LinkKind` is non-exhaustive, so matching on it needs a wildcard arm
+    let describe = |link: &Link<'_>| match link.kind() {
+        LinkKind::Url => "URL",
+        LinkKind::Email => "email",
+        _ => "other",
+    };
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Url, LinkKind::Email, LinkKind::Hashtag]);
+    let kinds: Vec<_> = finder
+        .links("http://example.org foo@example.com #tag")
+        .map(|link| describe(&link))
+        .collect();
+    assert_eq!(kinds, vec!["URL", "email", "other"]);
+}
+
+#[test]
+fn enable_and_disable_kind() {
+    let input = "http://example.com and foo@example.com";
+    let kinds = |finder: &LinkFinder| -> Vec<LinkKind> {
+        finder.links(input).map(|link| *link.kind()).collect()
+    };
+
+    let mut finder = LinkFinder::new();
+    assert_eq!(kinds(&finder), vec![LinkKind::Url, LinkKind::Email]);
+
+    finder.disable_kind(LinkKind::Email);
+    assert_eq!(kinds(&finder), vec![LinkKind::Url]);
+
+    finder.disable_kind(LinkKind::Url);
+    assert_eq!(kinds(&finder), vec![]);
+
+    finder.enable_kind(LinkKind::Email);
+    assert_eq!(kinds(&finder), vec![LinkKind::Email]);
+
+    // Other configuration is kept
+    finder.url_must_have_scheme(false);
+    finder.enable_kind(LinkKind::Url);
+    assert_eq!(
+        finder.links("example.org").next().map(|l| l.as_str()),
+        Some("example.org")
+    );
+}
+
+#[test]
+fn link_equality_and_hash() {
+    let finder = LinkFinder::new();
+    let text = "http://example.org and http://example.org";
+    let first: Vec<_> = finder.links(text).collect();
+    let second: Vec<_> = finder.links(text).collect();
+    assert_eq!(first, second);
+
+    // Same text at different offsets is not equal
+    assert_ne!(first[0], first[1]);
+
+    let set: HashSet<_> = first.into_iter().chain(second).collect();
+    assert_eq!(set.len(), 2);
+
+    // Same offsets in a different input with the same text are equal
+    let other = String::from("http://example.org");
+    let link = finder.links(&other).next().unwrap();
+    assert!(set.contains(&link));
+}
+
+#[test]
+fn scan_error() {
+    check_send::<ScanError>();
+    check_sync::<ScanError>();
+
+    let error = ScanError::from(io::Error::new(io::ErrorKind::UnexpectedEof, "oops"));
+    assert!(matches!(error, ScanError::Io(_)));
+    assert_eq!(error.to_string(), "failed to read input: oops");
+    assert!(error.source().is_some());
+
+    let bytes = vec![b'a', 0xff];
+    let utf8_error = std::str::from_utf8(&bytes).unwrap_err();
+    let error = ScanError::from(utf8_error);
+    assert!(matches!(error, ScanError::Utf8(_)));
+    assert!(error.to_string().starts_with("input is not valid UTF-8"));
+}
+
 fn check_send<T: Send>() {}
 
 fn check_sync<T: Sync>() {}
@@ -1,7 +1,13 @@
 mod common;
 
+use std::borrow::Cow;
+use std::sync::Arc;
+
 use crate::common::assert_linked_with;
-use linkify::{LinkFinder, LinkKind};
+use linkify::{
+    is_url, BalanceSet, CompatMode, LinkFinder, LinkKind, SchemeSet, UrlOptions, WhitespacePolicy,
+    UNICODE_BRACKET_PAIRS,
+};
 
 #[test]
 fn no_links() {
@@ -118,6 +124,39 @@ fn illegal_characters_stop_url() {
     assert_linked("http://example.org/foo|bar", "|http://example.org/foo||bar");
 }
 
+#[test]
+fn custom_path_terminators() {
+    let mut finder = LinkFinder::new();
+    assert_linked_with(
+        &finder,
+        "http://example.org/[a]@b",
+        "|http://example.org/[a]@b|",
+    );
+
+    finder.url_path_terminators(&[']', '@']);
+    assert_linked_with(
+        &finder,
+        "[url=http://example.org/a]b[/url]",
+        "[url=|http://example.org/a|]b[/url]",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/[a]",
+        "|http://example.org/[a|]",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/a@b",
+        "|http://example.org/a|@b",
+    );
+    // The default terminators still apply
+    assert_linked_with(
+        &finder,
+        "http://example.org/a<b|c",
+        "|http://example.org/a|<b|c",
+    );
+}
+
 #[test]
 fn illegal_characters_stop_url_without_protocol() {
     assert_urls_without_protocol("example.org/<", "|example.org/|<");
@@ -140,6 +179,21 @@ fn delimiter_at_end() {
     assert_linked("http://example.org/;", "|http://example.org/|;");
 }
 
+#[test]
+fn empty_query_or_fragment_after_authority() {
+    // An empty fragment is included, like after a path (`http://example.org/#`)
+    assert_linked("https://example.org#", "|https://example.org#|");
+    assert_linked("https://example.org#top", "|https://example.org#top|");
+    assert_linked("https://example.org#top.", "|https://example.org#top|.");
+    assert_urls_without_protocol("example.org#top", "|example.org#top|");
+    // An empty query is not, because a `?` at the end is more likely punctuation, like after a
+    // path (`http://example.org/?`)
+    assert_linked("https://example.org?", "|https://example.org|?");
+    assert_linked("(https://example.org?)", "(|https://example.org|?)");
+    assert_linked("https://example.org?#", "|https://example.org?#|");
+    assert_linked("https://example.org?a", "|https://example.org?a|");
+}
+
 #[test]
 fn delimiter_at_end_no_protocol() {
     assert_urls_without_protocol("example.org/.", "|example.org/|.");
@@ -193,6 +247,118 @@ fn matching_punctuation_tricky() {
     assert_linked("http://example.org/]()", "|http://example.org/|]()");
 }
 
+#[test]
+fn unmatched_closing_brackets() {
+    // A closing bracket without an opening bracket in the URL always ends it
+    assert_linked("http://example.org/a)", "|http://example.org/a|)");
+    assert_linked("http://example.org/a]", "|http://example.org/a|]");
+    assert_linked("http://example.org/a}", "|http://example.org/a|}");
+    assert_linked("http://example.org/a)b", "|http://example.org/a|)b");
+    assert_linked("http://example.org/a(b))", "|http://example.org/a(b)|)");
+    assert_linked("http://example.org/a[b)]", "|http://example.org/a[b|)]");
+}
+
+#[test]
+fn matching_punctuation_nested() {
+    assert_linked("((((http://x.org))))", "((((|http://x.org|))))");
+    assert_linked("((((http://x.org/))))", "((((|http://x.org/|))))");
+    assert_linked("http://x.org/((((a))))/b", "|http://x.org/((((a))))/b|");
+    assert_linked("(http://x.org/[{(a)}])", "(|http://x.org/[{(a)}]|)");
+    assert_linked("http://x.org/((((a))", "|http://x.org/((((a))|");
+    assert_linked("http://x.org/((a))))", "|http://x.org/((a))|))");
+
+    let deep = format!("http://x.org/{}a", "(".repeat(10_000));
+    let links: Vec<_> = LinkFinder::new().links(&deep).collect();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].as_str(), deep);
+}
+
+#[test]
+fn max_bracket_depth() {
+    let mut finder = LinkFinder::new();
+    finder.max_bracket_depth(2);
+    assert_linked_with(&finder, "http://x.org/((a))", "|http://x.org/((a))|");
+    assert_linked_with(&finder, "http://x.org/(a)(b)", "|http://x.org/(a)(b)|");
+    assert_linked_with(&finder, "http://x.org/([a])", "|http://x.org/([a])|");
+    assert_linked_with(&finder, "http://x.org/(((a)))", "|http://x.org/|(((a)))");
+    assert_linked_with(&finder, "http://x.org/a([{b}])", "|http://x.org/a|([{b}])");
+    assert_linked_with(&finder, "((((http://x.org/))))", "((((|http://x.org/|))))");
+
+    let deep = format!("http://x.org/a{}b", "(".repeat(10_000));
+    assert_eq!(
+        finder.links(&deep).next().unwrap().as_str(),
+        "http://x.org/a"
+    );
+
+    finder.max_bracket_depth(0);
+    assert_linked_with(&finder, "http://x.org/a(b)", "|http://x.org/a|(b)");
+}
+
+#[test]
+fn matching_punctuation_custom_pairs() {
+    assert_linked("«http://example.org»", "«|http://example.org»|");
+
+    let mut finder = LinkFinder::new();
+    finder.url_balance_pairs(&[('«', '»'), ('〈', '〉')]);
+    assert_linked_with(&finder, "«http://example.org»", "«|http://example.org|»");
+    assert_linked_with(
+        &finder,
+        "〈http://example.org/a〈b〉〉",
+        "〈|http://example.org/a〈b〉|〉",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/«a»/b",
+        "|http://example.org/«a»/b|",
+    );
+    // Opening brackets can't be at the end
+    assert_linked_with(&finder, "http://example.org/«", "|http://example.org/|«");
+    // The built-in brackets are still balanced
+    assert_linked_with(&finder, "(http://example.org/)", "(|http://example.org/|)");
+
+    finder.max_bracket_depth(1);
+    assert_linked_with(
+        &finder,
+        "http://example.org/(«a»)",
+        "|http://example.org/|(«a»)",
+    );
+}
+
+#[test]
+fn matching_punctuation_unicode_pairs() {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    finder.url_balance_pairs(UNICODE_BRACKET_PAIRS);
+    assert_linked_with(
+        &finder,
+        "【https://example.org/】",
+        "【|https://example.org/|】",
+    );
+    assert_linked_with(
+        &finder,
+        "見て【https://example.org/a】。",
+        "見て【|https://example.org/a|】。",
+    );
+    assert_linked_with(
+        &finder,
+        "⟨https://example.org/⟩",
+        "⟨|https://example.org/|⟩",
+    );
+    assert_linked_with(
+        &finder,
+        "（https://example.org/a（b））",
+        "（|https://example.org/a（b）|）",
+    );
+    assert_linked_with(
+        &finder,
+        "https://example.org/「a」/b",
+        "|https://example.org/「a」/b|",
+    );
+    // Also not part of the domain of URLs without a scheme
+    assert_linked_with(&finder, "【example.org】", "【|example.org|】");
+    assert_linked_with(&finder, "「example.org/a」", "「|example.org/a|」");
+}
+
 #[test]
 fn matching_punctuation_tricky_without_protocol() {
     assert_urls_without_protocol("((example.org/))", "((|example.org/|))");
@@ -207,6 +373,51 @@ fn matching_punctuation_tricky_without_protocol() {
     assert_urls_without_protocol("example.org/]()", "|example.org/|]()");
 }
 
+#[test]
+fn matching_punctuation_square_not_balanced() {
+    let mut finder = LinkFinder::new();
+    finder.url_balance_chars(BalanceSet::all().square(false));
+    assert_linked_with(
+        &finder,
+        "http://example.org/a[b]",
+        "|http://example.org/a|[b]",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/a]b",
+        "|http://example.org/a|]b",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/a(b)",
+        "|http://example.org/a(b)|",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/a{b}",
+        "|http://example.org/a{b}|",
+    );
+    // IPv6 hosts are not affected
+    assert_linked_with(&finder, "http://[::1]/a[b]", "|http://[::1]/a|[b]");
+}
+
+#[test]
+fn matching_punctuation_none_balanced() {
+    let mut finder = LinkFinder::new();
+    finder.url_balance_chars(BalanceSet::none());
+    assert_linked_with(
+        &finder,
+        "http://example.org/a(b)",
+        "|http://example.org/a|(b)",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/a{b}",
+        "|http://example.org/a|{b}",
+    );
+    assert_linked_with(&finder, "(http://example.org/)", "(|http://example.org/|)");
+}
+
 #[test]
 fn single_quote() {
     assert_linked("'https://example.org'", "'|https://example.org|'");
@@ -262,6 +473,28 @@ fn asterisk() {
     assert_linked("https://example.org/*/a", "|https://example.org/*/a|");
 }
 
+#[test]
+fn compat_mode_autolink_java() {
+    let mut finder = LinkFinder::new();
+    finder.compat_mode(CompatMode::AutolinkJava);
+    assert_linked_with(&finder, "https://example.org/*", "|https://example.org/*|");
+    assert_linked_with(
+        &finder,
+        "https://example.org/**.",
+        "|https://example.org/**|.",
+    );
+    // Single quotes are balanced like by default
+    assert_linked_with(&finder, "http://example.org/'.", "|http://example.org/|'.");
+    assert_linked_with(
+        &finder,
+        "'https://example.org/a*'",
+        "'|https://example.org/a*|'",
+    );
+
+    finder.compat_mode(CompatMode::Linkify);
+    assert_linked_with(&finder, "https://example.org/*", "|https://example.org/|*");
+}
+
 #[test]
 fn grave_quote_without_protocol() {
     // ` not allowed in URLs
@@ -289,6 +522,334 @@ fn html_no_protocol() {
     assert_urls_without_protocol("example.org</p>", "|example.org|</p>");
 }
 
+#[test]
+fn html_entities() {
+    let mut finder = LinkFinder::new();
+    finder.url_decode_html_entities(true);
+    assert_linked_with(
+        &finder,
+        "http://example.com/?a=1&amp;b=2",
+        "|http://example.com/?a=1&amp;b=2|",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.com/?a=1&amp;",
+        "|http://example.com/?a=1&amp;|",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.com/?a=1&amp;.",
+        "|http://example.com/?a=1&amp;|.",
+    );
+    assert_linked_with(
+        &finder,
+        "&lt;http://example.com/a&gt;",
+        "&lt;|http://example.com/a|&gt;",
+    );
+    assert_linked_with(
+        &finder,
+        "&quot;http://example.com/a&quot;",
+        "&quot;|http://example.com/a|&quot;",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.com/?a&b",
+        "|http://example.com/?a&b|",
+    );
+
+    // By default, entities are just text
+    assert_linked(
+        "http://example.com/?a=1&amp;",
+        "|http://example.com/?a=1&amp|;",
+    );
+    assert_linked("http://example.com/a&gt;", "|http://example.com/a&gt|;");
+}
+
+#[test]
+fn json_string_values_only() {
+    let mut finder = LinkFinder::new();
+    finder.json_string_values_only(true);
+    assert_linked_with(
+        &finder,
+        r#"{"https://a.com/" : "https://b.com/", "url":"https://c.com/"}"#,
+        r#"{"https://a.com/" : "|https://b.com/|", "url":"|https://c.com/|"}"#,
+    );
+    assert_linked_with(
+        &finder,
+        r#"["a@b.com", "say \"https://d.com/\" now"]"#,
+        r#"["|a@b.com|", "say \"|https://d.com/|\" now"]"#,
+    );
+    // Outside of strings and in strings that are not closed
+    assert_linked_with(
+        &finder,
+        r#"https://e.com/ {"k": 1} "https://f.com/"#,
+        r#"https://e.com/ {"k": 1} "|https://f.com/|"#,
+    );
+    // Links end at the end of the string
+    assert_linked_with(
+        &finder,
+        r#"{"a":"https://g.com/x","b":"y"}"#,
+        r#"{"a":"|https://g.com/x|","b":"y"}"#,
+    );
+    assert_linked_with(&finder, r#""" https://h.com/"#, r#""" https://h.com/"#);
+}
+
+#[test]
+fn json_unescape() {
+    let mut finder = LinkFinder::new();
+    finder.json_unescape(true);
+    assert_linked_with(
+        &finder,
+        r#"{"url":"https:\/\/example.org\/a\/b"}"#,
+        r#"{"url":"|https:\/\/example.org\/a\/b|"}"#,
+    );
+    assert_linked_with(
+        &finder,
+        r#"{"msg":"see https:\/\/caf\u00e9.example\/\ud83d\ude00x\nnext"}"#,
+        r#"{"msg":"see |https:\/\/caf\u00e9.example\/\ud83d\ude00x|\nnext"}"#,
+    );
+    // Escaped quotes and backslashes end links, including as `\u` escapes
+    assert_linked_with(
+        &finder,
+        r#""say \"https://a.org/\" or https://b.org/\u0022 or https://c.org/\\/d""#,
+        r#""say \"|https://a.org/|\" or |https://b.org/|\u0022 or |https://c.org/|\\/d""#,
+    );
+    // Invalid escapes are not decoded
+    assert_linked_with(
+        &finder,
+        r#""https:\/\/d.org\/\u00zz \udc00""#,
+        r#""|https:\/\/d.org\/|\u00zz \udc00""#,
+    );
+    assert_linked_with(&finder, r"a\u0040example.com", r"|a\u0040example.com|");
+
+    // The parts are parsed from the unescaped text
+    let link = finder.links(r"http:\/\/a.org\/x?q=\u0031").next().unwrap();
+    assert_eq!(link.as_str(), r"http:\/\/a.org\/x?q=\u0031");
+    assert_eq!(link.scheme(), Some("http"));
+    assert_eq!(link.host(), Some("a.org"));
+    assert_eq!(link.query(), Some("q=1"));
+
+    // Together with only finding links in string values
+    finder.json_string_values_only(true);
+    assert_linked_with(
+        &finder,
+        r#"{"https:\/\/a.org\/":"https:\/\/b.org\/"}"#,
+        r#"{"https:\/\/a.org\/":"|https:\/\/b.org\/|"}"#,
+    );
+
+    // Off by default
+    assert_linked(r"https:\/\/example.org", r"https:\/\/example.org");
+}
+
+#[test]
+fn skip_existing_anchors() {
+    let mut finder = LinkFinder::new();
+    finder.skip_existing_anchors(true);
+    assert_linked_with(
+        &finder,
+        "<a href=\"https://a.com/\">https://a.com/</a> https://b.com/",
+        "<a href=\"https://a.com/\">https://a.com/</a> |https://b.com/|",
+    );
+    assert_linked_with(
+        &finder,
+        "<p>https://a.com/ <A HREF='https://b.com/'>see <b>https://b.com/</b></A></p>",
+        "<p>|https://a.com/| <A HREF='https://b.com/'>see <b>https://b.com/</b></A></p>",
+    );
+    // Quoted attribute values can contain `>`
+    assert_linked_with(
+        &finder,
+        "<a title=\"a > b\" href=x>https://a.com/</a>",
+        "<a title=\"a > b\" href=x>https://a.com/</a>",
+    );
+    // Other tags that start with `a` are not anchors
+    assert_linked_with(
+        &finder,
+        "<abbr>https://a.com/</abbr> <area href=https://b.com/>",
+        "<abbr>|https://a.com/|</abbr> <area href=|https://b.com/|>",
+    );
+    // Nested anchors end at the outermost closing tag
+    assert_linked_with(
+        &finder,
+        "<a><a>https://a.com/</a>https://b.com/</a>https://c.com/",
+        "<a><a>https://a.com/</a>https://b.com/</a>|https://c.com/|",
+    );
+    // Stray closing tags are ignored, unclosed anchors continue up to a limit
+    assert_linked_with(
+        &finder,
+        "</a>https://a.com/ <a href=x>https://b.com/ a@example.com",
+        "</a>|https://a.com/| <a href=x>https://b.com/ a@example.com",
+    );
+    assert_linked_with(
+        &finder,
+        "https://a.com/ <a href=\"https://b.com/",
+        "|https://a.com/| <a href=\"https://b.com/",
+    );
+    assert_linked_with(&finder, "1 < 2 https://a.com/", "1 < 2 |https://a.com/|");
+    let filler = "\u{E4}".repeat(1500);
+    assert_linked_with(
+        &finder,
+        &format!("<a href=x>https://a.com/ {} https://b.com/", filler),
+        &format!("<a href=x>https://a.com/ {} |https://b.com/|", filler),
+    );
+    assert_linked_with(
+        &finder,
+        &format!("<a href=\"https://a.com/ {} https://b.com/", filler),
+        &format!("<a href=\"https://a.com/ {} |https://b.com/|", filler),
+    );
+
+    // By default, links in anchors are found
+    assert_linked(
+        "<a href=\"https://a.com/\">https://a.com/</a>",
+        "<a href=\"|https://a.com/|\">|https://a.com/|</a>",
+    );
+}
+
+#[test]
+fn decode_before_scan() {
+    let mut finder = LinkFinder::new();
+    finder.url_decode_before_scan(true);
+    let input = "go to http%3A%2F%2Fexample.org%2Fa%20b or https://b.com/%C3%A9 x%40example.com";
+    let links: Vec<_> = finder.links(input).collect();
+    let found: Vec<_> = links
+        .iter()
+        .map(|link| (link.as_str(), link.start(), link.end()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("http%3A%2F%2Fexample.org%2Fa", 6, 34),
+            ("https://b.com/%C3%A9", 42, 62),
+            ("x%40example.com", 63, 78)
+        ]
+    );
+    assert_eq!(links[0].kind(), &LinkKind::Url);
+    assert_eq!(links[0].scheme(), Some("http"));
+    assert_eq!(links[2].kind(), &LinkKind::Email);
+
+    // The parts are parsed from the decoded text
+    assert_eq!(links[0].authority(), Some("example.org"));
+    assert_eq!(links[0].host(), Some("example.org"));
+    assert_eq!(links[0].query(), None);
+    assert_eq!(links[2].host(), Some("example.com"));
+    let link = finder
+        .links("http%3A%2F%2Fu%40example.org%3A8080%2Fa%3Fb%3Dc%26d")
+        .next()
+        .unwrap();
+    assert_eq!(link.userinfo(), Some("u"));
+    assert_eq!(link.host(), Some("example.org"));
+    assert_eq!(link.port(), Some(8080));
+    assert_eq!(link.query(), Some("b=c&d"));
+    assert_eq!(
+        finder.hosts("http%3A%2F%2Fexample.org%2Fa"),
+        vec!["example.org"]
+    );
+
+    // Nested URLs are found in the decoded query, with positions in the input
+    finder.extract_nested_urls(true);
+    let input = "http%3A%2F%2Fa.org%2F%3Fu%3Dhttp%3A%2F%2Fb.org%2Fx";
+    let links: Vec<_> = finder.links(input).collect();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[1].as_str(), "http%3A%2F%2Fb.org%2Fx");
+    assert_eq!(links[1].start(), 28);
+    assert_eq!(links[1].host(), Some("b.org"));
+
+    // Off by default
+    assert_linked("http%3A%2F%2Fexample.org", "http%3A%2F%2Fexample.org");
+}
+
+#[test]
+fn normalize_fullwidth() {
+    let mut finder = LinkFinder::new();
+    finder.normalize_fullwidth(true);
+    assert_linked_with(
+        &finder,
+        "見て：ｈｔｔｐ：／／ｅｘａｍｐｌｅ．ｃｏｍ／ａ． 次",
+        "見て：|ｈｔｔｐ：／／ｅｘａｍｐｌｅ．ｃｏｍ／ａ|． 次",
+    );
+    assert_linked_with(
+        &finder,
+        "（ｈｔｔｐｓ：／／ｅｘａｍｐｌｅ．ｃｏｍ）",
+        "（|ｈｔｔｐｓ：／／ｅｘａｍｐｌｅ．ｃｏｍ|）",
+    );
+    assert_linked_with(
+        &finder,
+        "https://ｅｘａｍｐｌｅ．ｃｏｍ/x",
+        "|https://ｅｘａｍｐｌｅ．ｃｏｍ/x|",
+    );
+    assert_linked_with(
+        &finder,
+        "ａ＠ｅｘａｍｐｌｅ．ｃｏｍ",
+        "|ａ＠ｅｘａｍｐｌｅ．ｃｏｍ|",
+    );
+
+    let link = finder.links("ｈｔｔｐ：／／ａ．ｏｒｇ").next().unwrap();
+    assert_eq!(link.as_str(), "ｈｔｔｐ：／／ａ．ｏｒｇ");
+    assert_eq!(link.scheme(), Some("http"));
+
+    // The parts are parsed from the normalized text
+    let link = finder
+        .links("ｈｔｔｐ：／／ｅｘａｍｐｌｅ．ｃｏｍ／ａ？ｂ＝ｃ")
+        .next()
+        .unwrap();
+    assert_eq!(link.scheme(), Some("http"));
+    assert_eq!(link.host(), Some("example.com"));
+    assert_eq!(link.query(), Some("b=c"));
+    let link = finder.links("ａ＠ｅｘａｍｐｌｅ．ｃｏｍ").next().unwrap();
+    assert_eq!(link.host(), Some("example.com"));
+
+    // Together with decoding percent-encoding
+    finder.url_decode_before_scan(true);
+    assert_linked_with(
+        &finder,
+        "ｈｔｔｐ%3A／／ａ．ｏｒｇ",
+        "|ｈｔｔｐ%3A／／ａ．ｏｒｇ|",
+    );
+
+    // Off by default
+    assert_not_linked("ｈｔｔｐ：／／ｅｘａｍｐｌｅ．ｃｏｍ");
+}
+
+#[test]
+fn html_entities_output() {
+    let mut finder = LinkFinder::new();
+    finder.url_decode_html_entities(true);
+    assert_linked_with(
+        &finder,
+        "http://example.com/?a=1&#38;b=2&#x26;",
+        "|http://example.com/?a=1&#38;b=2&#x26;|",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.com/a&#60;b&#32;c",
+        "|http://example.com/a|&#60;b&#32;c",
+    );
+
+    let input =
+        "http://example.com/?a=1&amp;b=2 &quot;http://example.com/?c&amp;d=&#39;e&#x27;&quot; \
+                 http://example.com/?f&g&amp a@example.com";
+    let links: Vec<_> = finder.links(input).collect();
+    let outputs: Vec<_> = links.iter().map(|link| link.output()).collect();
+    assert_eq!(
+        outputs,
+        vec![
+            "http://example.com/?a=1&b=2",
+            "http://example.com/?c&d='e'",
+            "http://example.com/?f&g&amp",
+            "a@example.com"
+        ]
+    );
+    assert_eq!(links[0].as_str(), "http://example.com/?a=1&amp;b=2");
+    assert!(matches!(outputs[2], Cow::Borrowed(_)));
+
+    // Without the option, output is the same as the input
+    let finder = LinkFinder::new();
+    let link = finder
+        .links("http://example.com/?a=1&amp;b=2")
+        .next()
+        .unwrap();
+    assert_eq!(link.output(), "http://example.com/?a=1&amp;b=2");
+}
+
 #[test]
 fn css() {
     assert_linked("http://example.org\");", "|http://example.org|\");");
@@ -402,37 +963,80 @@ fn international() {
 }
 
 #[test]
-fn international_not_allowed() {
+fn trim_trailing_symbols() {
     let mut finder = LinkFinder::new();
-    finder.url_can_be_iri(false);
-    finder.url_must_have_scheme(true);
-    finder.kinds(&[LinkKind::Url]);
-    assert_linked_with(&finder, "http://üñîçøðé.com", "http://üñîçøðé.com");
-    assert_linked_with(&finder, "http://üñîçøðé.com/ä", "http://üñîçøðé.com/ä");
+    finder.url_trim_trailing_symbols(true);
     assert_linked_with(
         &finder,
-        "http://example.org/\u{A1}",
-        "|http://example.org/|\u{A1}",
+        "http://example.org\u{1F44D}",
+        "|http://example.org|\u{1F44D}",
     );
     assert_linked_with(
         &finder,
-        "http://example.org/\u{A2}",
-        "|http://example.org/|\u{A2}",
+        "http://example.org/\u{1F44D}",
+        "|http://example.org/|\u{1F44D}",
     );
     assert_linked_with(
         &finder,
-        "http://example.org/\u{1F600}",
-        "|http://example.org/|\u{1F600}",
+        "http://example.org/a\u{1F44D}\u{1F3FD}!",
+        "|http://example.org/a|\u{1F44D}\u{1F3FD}!",
     );
     assert_linked_with(
         &finder,
-        "http://example.org/\u{A2}/",
-        "|http://example.org/|\u{A2}/",
+        "http://example.org/a\u{2764}\u{FE0F}",
+        "|http://example.org/a|\u{2764}\u{FE0F}",
     );
     assert_linked_with(
         &finder,
-        "http://xn--c1h.example.com/",
-        "|http://xn--c1h.example.com/|",
+        "http://example.org/\u{1F600}/a",
+        "|http://example.org/\u{1F600}/a|",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/\u{1F600}/",
+        "|http://example.org/\u{1F600}/|",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/a\u{A9}b",
+        "|http://example.org/a\u{A9}b|",
+    );
+    // Other international characters are not affected
+    assert_linked_with(&finder, "http://example.org/ä", "|http://example.org/ä|");
+}
+
+#[test]
+fn international_not_allowed() {
+    let mut finder = LinkFinder::new();
+    finder.url_can_be_iri(false);
+    finder.url_must_have_scheme(true);
+    finder.kinds(&[LinkKind::Url]);
+    assert_linked_with(&finder, "http://üñîçøðé.com", "http://üñîçøðé.com");
+    assert_linked_with(&finder, "http://üñîçøðé.com/ä", "http://üñîçøðé.com/ä");
+    assert_linked_with(
+        &finder,
+        "http://example.org/\u{A1}",
+        "|http://example.org/|\u{A1}",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/\u{A2}",
+        "|http://example.org/|\u{A2}",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/\u{1F600}",
+        "|http://example.org/|\u{1F600}",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org/\u{A2}/",
+        "|http://example.org/|\u{A2}/",
+    );
+    assert_linked_with(
+        &finder,
+        "http://xn--c1h.example.com/",
+        "|http://xn--c1h.example.com/|",
     );
 }
 
@@ -517,6 +1121,30 @@ E-Mail:
     );
 }
 
+#[test]
+fn email_followed_by_path() {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+
+    assert_linked_with(&finder, "foo@bar.com/baz", "|foo@bar.com|/baz");
+    assert_linked_with(
+        &finder,
+        "foo@bar.com/baz.html example.org/a",
+        "|foo@bar.com|/baz.html |example.org/a|",
+    );
+    // The rest is not a separate email either
+    assert_linked_with(
+        &finder,
+        "foo@bar.com/baz@qux.com",
+        "|foo@bar.com|/baz@qux.com",
+    );
+    assert_linked_with(
+        &finder,
+        "foo@bar.com /baz@qux.com",
+        "|foo@bar.com| |/baz@qux.com|",
+    );
+}
+
 #[test]
 fn uri_with_empty_path_and_with_query() {
     assert_linked(
@@ -529,6 +1157,274 @@ fn uri_with_empty_path_and_with_query() {
     );
 }
 
+#[test]
+fn scheme_relative() {
+    let mut finder = LinkFinder::new();
+    finder.url_allow_scheme_relative(true);
+    assert_linked_with(&finder, "//example.org/path", "|//example.org/path|");
+    assert_linked_with(
+        &finder,
+        r#"<script src="//cdn.example.org/x"></script>"#,
+        r#"<script src="|//cdn.example.org/x|"></script>"#,
+    );
+    assert_linked_with(
+        &finder,
+        "src='//cdn.example.org/x'",
+        "src='|//cdn.example.org/x|'",
+    );
+    assert_linked_with(&finder, "(//example.org/a)", "(|//example.org/a|)");
+    assert_linked_with(&finder, "//[::1]:8080/", "|//[::1]:8080/|");
+    assert_linked_with(
+        &finder,
+        "http://example.org/ //example.com/",
+        "|http://example.org/| |//example.com/|",
+    );
+
+    // Needs to be at the start of a word and have a domain
+    assert_linked_with(&finder, "a//example.org/", "a//example.org/");
+    assert_linked_with(&finder, "///example.org/", "///example.org/");
+    assert_linked_with(&finder, "1abc://example.org/", "1abc://example.org/");
+    assert_linked_with(&finder, "// comment", "// comment");
+    assert_linked_with(&finder, "//foo/bar", "//foo/bar");
+
+    // Not found by default
+    assert_not_linked("//example.org/path");
+}
+
+#[test]
+fn scheme() {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    finder.url_allow_scheme_relative(true);
+    let links: Vec<_> = finder
+        .links("https://example.org/ example.com //example.net/ a@example.org")
+        .collect();
+    assert_eq!(links.len(), 4);
+
+    assert_eq!(links[0].scheme(), Some("https"));
+    assert!(!links[0].is_scheme_relative());
+    assert_eq!(links[1].scheme(), None);
+    assert!(!links[1].is_scheme_relative());
+    assert_eq!(links[2].scheme(), None);
+    assert!(links[2].is_scheme_relative());
+    assert_eq!(links[3].scheme(), None);
+    assert!(!links[3].is_scheme_relative());
+}
+
+#[test]
+fn with_default_scheme() {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    finder.url_allow_scheme_relative(true);
+    finder.detect_scp_urls(true);
+    let links: Vec<String> = finder
+        .links("example.com/foo http://a.org //b.org/x a@example.org git@c.org:o/r.git")
+        .map(|link| link.with_default_scheme("https"))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            "https://example.com/foo",
+            "http://a.org",
+            "https://b.org/x",
+            "a@example.org",
+            "git@c.org:o/r.git",
+        ]
+    );
+}
+
+#[test]
+fn lowercase_scheme_in_output() {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    finder.lowercase_scheme_in_output(true);
+    let links: Vec<_> = finder
+        .links("HTTP://Example.com/X Https://a.org example.NET/Y")
+        .collect();
+    assert_eq!(links[0].as_str(), "HTTP://Example.com/X");
+    assert_eq!(links[0].output(), "http://Example.com/X");
+    assert!(matches!(links[0].output(), Cow::Owned(_)));
+    assert_eq!(links[1].output(), "https://a.org");
+    assert_eq!(links[2].output(), "example.NET/Y");
+    assert!(matches!(links[2].output(), Cow::Borrowed(_)));
+
+    let finder = LinkFinder::new();
+    let link = finder.links("HTTP://Example.com/X").next().unwrap();
+    assert_eq!(link.output(), "HTTP://Example.com/X");
+    assert!(matches!(link.output(), Cow::Borrowed(_)));
+}
+
+#[test]
+fn is_url_whole_string() {
+    let options = UrlOptions::new();
+    assert!(is_url("http://example.org", &options));
+    assert!(is_url("https://example.org/a(b)?c=d#e", &options));
+    assert!(is_url("http://üñîçøðé.com/ä", &options));
+    assert!(is_url("ab://c", &options));
+    assert!(!is_url("http://example.org ", &options));
+    assert!(!is_url(" http://example.org", &options));
+    assert!(!is_url("http://example.org/.", &options));
+    assert!(!is_url("(http://example.org/)", &options));
+    assert!(!is_url("http://a.org http://b.org", &options));
+    assert!(!is_url("example.org", &options));
+    assert!(!is_url("foo@example.org", &options));
+    assert!(!is_url("", &options));
+
+    let mut options = UrlOptions::new();
+    options.must_have_scheme(false);
+    assert!(is_url("example.org", &options));
+    assert!(is_url("example.org/foo", &options));
+    assert!(is_url("http://example.org", &options));
+    assert!(!is_url("example.org.", &options));
+    assert!(!is_url("foo@example.org", &options));
+
+    let mut options = UrlOptions::new();
+    options.can_be_iri(false);
+    assert!(!is_url("http://üñîçøðé.com", &options));
+    assert!(is_url("http://example.com", &options));
+}
+
+#[test]
+fn finder_is_url() {
+    let mut finder = LinkFinder::new();
+    assert!(finder.is_url("https://example.org/a(b)?c=d#e"));
+    assert!(!finder.is_url("http://example.org "));
+    assert!(!finder.is_url("(http://example.org/)"));
+    assert!(!finder.is_url("http://a.org http://b.org"));
+    assert!(!finder.is_url("foo@example.org"));
+    assert!(!finder.is_url(""));
+
+    finder.extract_nested_urls(true);
+    assert!(finder.is_url("https://a.org/?u=https://b.org"));
+
+    finder.url_must_have_scheme(false);
+    assert!(finder.is_url("example.org/foo"));
+    finder.url_min_host_length(20);
+    assert!(!finder.is_url("example.org/foo"));
+
+    finder.url_can_be_iri(false);
+    assert!(!finder.is_url("http://üñîçøðé.com"));
+
+    finder.kinds(&[LinkKind::Email]);
+    assert!(!finder.is_url("http://example.com"));
+}
+
+#[test]
+fn query_pairs() {
+    let finder = LinkFinder::new();
+    let pairs = |input: &str| -> Vec<(String, String)> {
+        let link = finder.links(input).next().unwrap();
+        link.query_pairs()
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect()
+    };
+    let pair = |key: &str, value: &str| (key.to_string(), value.to_string());
+
+    assert_eq!(
+        pairs("upi://pay?pa=X&pn=Y"),
+        vec![pair("pa", "X"), pair("pn", "Y")]
+    );
+    assert_eq!(
+        pairs("https://example.org/?a=1;b=&c&=d#e=f"),
+        vec![pair("a", "1"), pair("b", ""), pair("c", ""), pair("", "d")]
+    );
+    assert_eq!(
+        pairs("https://example.org/?%C3%A4=a%26b=c+d"),
+        vec![pair("\u{E4}", "a&b=c+d")]
+    );
+    assert_eq!(pairs("https://example.org/a#b?c=d"), vec![]);
+    assert_eq!(pairs("https://example.org/?"), vec![]);
+
+    let link = finder.links("https://example.org/?a=1#top").next().unwrap();
+    assert_eq!(link.query(), Some("a=1"));
+    assert!(matches!(
+        link.query_pairs().next(),
+        Some((Cow::Borrowed(_), Cow::Borrowed(_)))
+    ));
+    let link = finder.links("https://example.org/").next().unwrap();
+    assert_eq!(link.query(), None);
+    let link = finder.links("a@example.org?a=b").next().unwrap();
+    assert_eq!(link.query(), None);
+}
+
+#[test]
+fn nested_urls() {
+    let mut finder = LinkFinder::new();
+    finder.extract_nested_urls(true);
+
+    let links = |input| -> Vec<String> {
+        finder
+            .links(input)
+            .map(|link| link.as_str().to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        links("https://a.com/?next=https://b.com/path"),
+        vec![
+            "https://a.com/?next=https://b.com/path",
+            "https://b.com/path"
+        ]
+    );
+    assert_eq!(
+        links("https://a.com/?x=1&next=https://b.com/p&y=2#frag, http://c.com"),
+        vec![
+            "https://a.com/?x=1&next=https://b.com/p&y=2#frag",
+            "https://b.com/p",
+            "http://c.com"
+        ]
+    );
+    assert_eq!(
+        links("https://a.com/?u=https%3A%2F%2Fb.com%2Fpath&x=1"),
+        vec![
+            "https://a.com/?u=https%3A%2F%2Fb.com%2Fpath&x=1",
+            "https%3A%2F%2Fb.com%2Fpath"
+        ]
+    );
+    assert_eq!(
+        links("https://a.com/?u=https://b.com/?u=https://c.com/?u=https://d.com/?u=https://e.com/"),
+        vec![
+            "https://a.com/?u=https://b.com/?u=https://c.com/?u=https://d.com/?u=https://e.com/",
+            "https://b.com/?u=https://c.com/?u=https://d.com/?u=https://e.com/",
+            "https://c.com/?u=https://d.com/?u=https://e.com/",
+            "https://d.com/?u=https://e.com/"
+        ]
+    );
+    // Not a URL, or not the whole value
+    assert_eq!(
+        links("https://a.com/?a=b&c=x:y&d=1https://b.com/"),
+        vec!["https://a.com/?a=b&c=x:y&d=1https://b.com/"]
+    );
+
+    let nested = finder
+        .links("https://a.com/?next=https://b.com/path")
+        .nth(1)
+        .unwrap();
+    assert_eq!(nested.start(), 20);
+    assert_eq!(nested.scheme(), Some("https"));
+    let encoded = finder
+        .links("https://a.com/?u=https%3A%2F%2Fb.com%2Fpath%3Fq%3D1")
+        .nth(1)
+        .unwrap();
+    assert_eq!(encoded.host(), Some("b.com"));
+    assert_eq!(encoded.query(), Some("q=1"));
+
+    // Spans don't include the nested URLs
+    assert_linked_with(
+        &finder,
+        "see https://a.com/?next=https://b.com/path.",
+        "see |https://a.com/?next=https://b.com/path|.",
+    );
+
+    // Not extracted by default
+    assert_eq!(
+        LinkFinder::new()
+            .links("https://a.com/?next=https://b.com/path")
+            .count(),
+        1
+    );
+}
+
 #[test]
 fn fuzz() {
     assert_not_linked("ab:/ϸ");
@@ -635,6 +1531,43 @@ fn ipv6_various_schemes() {
     );
 }
 
+#[test]
+fn version_control_urls() {
+    assert_linked(
+        "ssh://git@github.com/owner/repo.git",
+        "|ssh://git@github.com/owner/repo.git|",
+    );
+    // Only the trailing dot is trimmed, not the `.git`
+    assert_linked(
+        "Clone ssh://git@github.com/owner/repo.git.",
+        "Clone |ssh://git@github.com/owner/repo.git|.",
+    );
+    assert_linked(
+        "(ssh://git@example.org:2222/owner/repo.git)",
+        "(|ssh://git@example.org:2222/owner/repo.git|)",
+    );
+    assert_linked(
+        "git+ssh://git@example.org/a/b.git, then",
+        "|git+ssh://git@example.org/a/b.git|, then",
+    );
+    assert_linked("ssh://git@[::1]/repo.git", "|ssh://git@[::1]/repo.git|");
+    assert_linked(
+        "ssh://git@[::1]:2222/repo.git",
+        "|ssh://git@[::1]:2222/repo.git|",
+    );
+    assert_not_linked("ssh://git@[::1/repo.git");
+
+    let finder = LinkFinder::new();
+    let links: Vec<_> = finder
+        .links("ssh://git@github.com/owner/repo.git ssh://git@[::1]:2222/repo.git")
+        .collect();
+    assert_eq!(links[0].userinfo(), Some("git"));
+    assert_eq!(links[0].host(), Some("github.com"));
+    assert_eq!(links[1].userinfo(), Some("git"));
+    assert_eq!(links[1].host(), Some("[::1]"));
+    assert_eq!(links[1].port(), Some(2222));
+}
+
 #[test]
 fn ipv6_yggdrasil_addresses() {
     // Yggdrasil mesh network addresses (0200::/7)
@@ -864,6 +1797,668 @@ fn non_breaking_space_iri_disabled() {
     );
 }
 
+#[test]
+fn scheme_set() {
+    let schemes = Arc::new(SchemeSet::new().allow_only(&["http", "HTTPS"]));
+    let finder = LinkFinder::with_schemes(schemes.clone());
+    assert_linked_with(
+        &finder,
+        "http://a.org https://b.org HTTP://c.org ftp://d.org xhttp://e.org",
+        "|http://a.org| |https://b.org| |HTTP://c.org| ftp://d.org xhttp://e.org",
+    );
+    // Other options still apply, and the set is shared between finders
+    let mut finder = LinkFinder::with_schemes(schemes.clone());
+    finder.url_must_have_scheme(false);
+    assert_linked_with(&finder, "ftp://d.org e.org", "ftp://d.org |e.org|");
+    assert_eq!(Arc::strong_count(&schemes), 3);
+
+    let schemes = Arc::new(SchemeSet::new().require_host(&["myapp"]));
+    let finder = LinkFinder::with_schemes(schemes);
+    assert_linked_with(
+        &finder,
+        "myapp://+1999 myapp://example.org foo://+1999",
+        "myapp://+1999 |myapp://example.org| |foo://+1999|",
+    );
+    // The default schemes still require a host
+    assert_linked_with(&finder, "https://+1999", "https://+1999");
+}
+
+#[test]
+fn scheme_set_keep_sub_delims() {
+    let schemes = SchemeSet::new().keep_sub_delims(&["custom"], &[';', '=']);
+    let finder = LinkFinder::with_schemes(Arc::new(schemes));
+    assert_linked_with(&finder, "custom://a;", "|custom://a;|");
+    assert_linked_with(
+        &finder,
+        "see custom://host;v=1/path, then",
+        "see |custom://host;v=1/path|, then",
+    );
+    // Other sub-delims end the authority
+    assert_linked_with(&finder, "custom://a;b,c/x", "|custom://a;b|,c/x");
+    assert_linked_with(&finder, "(custom://a;b)", "(|custom://a;b|)");
+
+    let link = finder.links("CUSTOM://host;v=1").next().unwrap();
+    assert_eq!(link.authority(), Some("host;v=1"));
+
+    // Only for the specified schemes, and not for schemes that require a host
+    assert_linked_with(&finder, "other://a;", "|other://a|;");
+    assert_linked_with(&finder, "other://a,b", "|other://a,b|");
+    assert_linked_with(&finder, "http://a.com;b/", "http://a.com;b/");
+    assert_linked("custom://a;", "|custom://a|;");
+}
+
+#[test]
+fn scheme_set_slashes_optional() {
+    let schemes = SchemeSet::new().slashes_optional(&["urn", "MAILTO", "ssh", "http"]);
+    assert!(!schemes.requires_slashes("urn"));
+    assert!(!schemes.requires_slashes("mailto"));
+    assert!(schemes.requires_slashes("ssh"));
+    assert!(schemes.requires_slashes("foo"));
+
+    let finder = LinkFinder::with_schemes(Arc::new(schemes));
+    assert_linked_with(
+        &finder,
+        "See urn:isbn:0451450523.",
+        "See |urn:isbn:0451450523|.",
+    );
+    assert_linked_with(
+        &finder,
+        "(mailto:foo@example.org?subject=hi)",
+        "(|mailto:foo@example.org?subject=hi|)",
+    );
+    assert_linked_with(&finder, "urn://example.org/a", "|urn://example.org/a|");
+    assert_linked_with(&finder, "urn: nothing", "urn: nothing");
+    assert_linked_with(&finder, "xurn:a foo:bar", "xurn:a foo:bar");
+    // Schemes that require a host always need `//`
+    assert_linked_with(
+        &finder,
+        "http:example.org ssh:example.org ssh://example.org",
+        "http:example.org ssh:example.org |ssh://example.org|",
+    );
+
+    let link = finder.links("urn:isbn:0451450523").next().unwrap();
+    assert_eq!(link.scheme(), Some("urn"));
+    assert_eq!(link.host(), None);
+}
+
+#[test]
+fn browser_internal_schemes() {
+    // URLs with an authority are found by default
+    assert_linked("chrome://settings/", "|chrome://settings/|");
+    assert_linked("(edge://flags/#foo)", "(|edge://flags/#foo|)");
+    assert_linked("about:blank", "about:blank");
+
+    let finder = LinkFinder::with_schemes(Arc::new(SchemeSet::new().slashes_optional(&["about"])));
+    assert_linked_with(
+        &finder,
+        "Open about:blank, then chrome://settings/.",
+        "Open |about:blank|, then |chrome://settings/|.",
+    );
+    assert_linked_with(&finder, "about:config#foo.", "|about:config#foo|.");
+    assert_linked_with(&finder, "about:", "about:");
+    let link = finder.links("about:blank").next().unwrap();
+    assert_eq!(link.scheme(), Some("about"));
+    assert_eq!(link.host(), None);
+}
+
+#[test]
+fn browser_schemes() {
+    let finder = LinkFinder::with_schemes(Arc::new(SchemeSet::new().browser_schemes()));
+    assert_linked_with(
+        &finder,
+        "Set it in about:config, or chrome://flags/#enable-foo.",
+        "Set it in |about:config|, or |chrome://flags/#enable-foo|.",
+    );
+    assert_linked_with(
+        &finder,
+        "(view-source:https://example.org/a)",
+        "(|view-source:https://example.org/a|)",
+    );
+    assert_linked_with(&finder, "Think about: it", "Think about: it");
+
+    // Opt-in
+    assert_linked("about:config", "about:config");
+    assert_linked(
+        "view-source:https://example.org/",
+        "view-source:|https://example.org/|",
+    );
+}
+
+#[test]
+#[cfg(feature = "url")]
+fn resolve_base() {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    finder.url_allow_scheme_relative(true);
+    let input = "https://a.com/x //cdn.example.org/lib.js example.org/a?b a@example.com";
+
+    let resolved: Vec<_> = finder.links(input).map(|link| link.resolved()).collect();
+    assert_eq!(
+        resolved,
+        vec![Some(Cow::Borrowed("https://a.com/x")), None, None, None]
+    );
+
+    finder
+        .resolve_base("http://site.com/dir/page.html")
+        .unwrap();
+    let resolved: Vec<_> = finder.links(input).map(|link| link.resolved()).collect();
+    assert_eq!(
+        resolved,
+        vec![
+            Some(Cow::Borrowed("https://a.com/x")),
+            Some(Cow::Owned("http://cdn.example.org/lib.js".to_string())),
+            Some(Cow::Owned("http://example.org/a?b".to_string())),
+            None
+        ]
+    );
+
+    assert!(finder.resolve_base("/relative").is_err());
+}
+
+#[test]
+fn terminating_whitespace() {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    assert_linked_with(
+        &finder,
+        "http://example.org/a\u{A0}b http://example.org/c\u{200B}d",
+        "|http://example.org/a|\u{A0}b |http://example.org/c\u{200B}d|",
+    );
+
+    finder.url_terminating_whitespace(WhitespacePolicy::AsciiOnly);
+    assert_linked_with(
+        &finder,
+        "http://example.org/a\u{A0}b http://example.org/c d",
+        "|http://example.org/a\u{A0}b| |http://example.org/c| d",
+    );
+    // Also in the host, with and without a scheme
+    assert_linked_with(
+        &finder,
+        "http://exa\u{A0}mple.org/a x\u{A0}example.org",
+        "|http://exa\u{A0}mple.org/a| |x\u{A0}example.org|",
+    );
+
+    // The characters can be chosen at runtime
+    let chars: Vec<char> = "\u{200B}".chars().collect();
+    finder.url_terminating_whitespace(WhitespacePolicy::Custom(chars));
+    assert_linked_with(
+        &finder,
+        "http://example.org/a\u{A0}b http://example.org/c\u{200B}d e\tf",
+        "|http://example.org/a\u{A0}b| |http://example.org/c|\u{200B}d e\tf",
+    );
+    assert_linked_with(
+        &finder,
+        "http://example.org\u{200B}x example.org\u{200B}/x",
+        "|http://example.org|\u{200B}x |example.org|\u{200B}/x",
+    );
+    assert_linked_with(
+        &finder,
+        "http://exa\u{A0}mple.org/a x\u{A0}example.org",
+        "|http://exa\u{A0}mple.org/a| |x\u{A0}example.org|",
+    );
+    assert_linked_with(&finder, "\u{200B}.example.org", "\u{200B}.example.org");
+    // The whitespace separates words like a space
+    assert_linked_with(&finder, "a ex\u{200B}ample.org", "a ex\u{200B}|ample.org|");
+}
+
+#[test]
+fn strip_zero_width() {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    let input =
+        "http://exa\u{200B}mple.org/a ex\u{200C}am\u{200D}ple.org \u{FEFF}http://example.org";
+    let outputs: Vec<_> = finder.links(input).map(|link| link.output()).collect();
+    assert_eq!(
+        outputs,
+        vec![
+            "http://exa\u{200B}mple.org/a",
+            "ex\u{200C}am\u{200D}ple.org",
+            "http://example.org"
+        ]
+    );
+
+    finder.url_strip_zero_width(true);
+    let links: Vec<_> = finder.links(input).collect();
+    let outputs: Vec<_> = links.iter().map(|link| link.output()).collect();
+    assert_eq!(
+        outputs,
+        vec!["http://example.org/a", "example.org", "http://example.org"]
+    );
+    assert!(matches!(outputs[2], Cow::Borrowed(_)));
+    assert_eq!(links[0].as_str(), "http://exa\u{200B}mple.org/a");
+    assert_eq!(links[0].host(), Some("exa\u{200B}mple.org"));
+
+    finder.lowercase_scheme_in_output(true);
+    let link = finder.links("HTTP://exa\u{200B}mple.org").next().unwrap();
+    assert_eq!(link.output(), "http://example.org");
+
+    // Only URLs are affected
+    let link = finder.links("a@exa\u{200B}mple.org").next().unwrap();
+    assert_eq!(link.output(), "a@exa\u{200B}mple.org");
+}
+
+#[test]
+fn reject_emoji_host() {
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    finder.url_allow_scheme_relative(true);
+    assert_linked_with(
+        &finder,
+        "http://\u{1F600}.example.com/ http://example.org/\u{1F600}",
+        "|http://\u{1F600}.example.com/| |http://example.org/\u{1F600}|",
+    );
+
+    finder.url_reject_emoji_host(true);
+    // Host
+    assert_linked_with(
+        &finder,
+        "http://\u{1F600}.example.com/",
+        "http://\u{1F600}.example.com/",
+    );
+    assert_linked_with(&finder, "\u{1F600}.example.com", "\u{1F600}.example.com");
+    assert_linked_with(&finder, "//\u{2764}.example.com", "//\u{2764}.example.com");
+    assert_linked_with(
+        &finder,
+        "http://example\u{1F600}.com/",
+        "|http://example|\u{1F600}.com/",
+    );
+    // Path, query and fragment
+    assert_linked_with(
+        &finder,
+        "http://example.org/\u{1F600}?q=\u{1F600}#\u{1F600}",
+        "|http://example.org/\u{1F600}?q=\u{1F600}#\u{1F600}|",
+    );
+    assert_linked_with(&finder, "example.org/\u{1F600}", "|example.org/\u{1F600}|");
+    // Other international characters are still allowed in the host
+    assert_linked_with(
+        &finder,
+        "http://\u{E4}.example.com/",
+        "|http://\u{E4}.example.com/|",
+    );
+}
+
+#[test]
+fn known_hosts() {
+    let mut finder = LinkFinder::new();
+    finder.url_known_hosts(&["wiki", "Build.corp.x1"]);
+    assert_linked_with(&finder, "see wiki", "see |wiki|");
+    assert_linked_with(&finder, "see wiki/Home.", "see |wiki/Home|.");
+    assert_linked_with(&finder, "WIKI:8080/a?b=c", "|WIKI:8080/a?b=c|");
+    assert_linked_with(&finder, "build.corp.x1/42", "|build.corp.x1/42|");
+    assert_linked_with(&finder, "(wiki/a)", "(|wiki/a|)");
+    assert_linked_with(&finder, "\"wiki/a\" b", "\"|wiki/a|\" b");
+    assert_linked_with(
+        &finder,
+        "http://build.corp.x1/42 https://wiki/Home",
+        "|http://build.corp.x1/42| |https://wiki/Home|",
+    );
+
+    // Only as a whole host
+    assert_linked_with(&finder, "mywiki wiki2 wiki-a", "mywiki wiki2 wiki-a");
+    assert_linked_with(&finder, "wiki.example.org", "wiki.example.org");
+    assert_linked_with(&finder, "a@wiki x.wiki", "a@wiki x.wiki");
+    assert_linked_with(
+        &finder,
+        "http://example.org/wiki/a",
+        "|http://example.org/wiki/a|",
+    );
+
+    // Without known hosts, these are not found
+    let finder = LinkFinder::new();
+    assert_linked_with(&finder, "see wiki/Home", "see wiki/Home");
+    assert_linked_with(
+        &finder,
+        "http://build.corp.x1/42",
+        "http://build.corp.x1/42",
+    );
+}
+
+#[test]
+fn scp_urls() {
+    let mut finder = LinkFinder::new();
+    assert_linked_with(
+        &finder,
+        "git@github.com:owner/repo.git",
+        "|git@github.com|:owner/repo.git",
+    );
+
+    finder.detect_scp_urls(true);
+    assert_linked_with(
+        &finder,
+        "clone git@github.com:owner/repo.git.",
+        "clone |git@github.com:owner/repo.git|.",
+    );
+    assert_linked_with(
+        &finder,
+        "(git@example.org:/srv/repo)",
+        "(|git@example.org:/srv/repo|)",
+    );
+    assert_linked_with(
+        &finder,
+        "me@example.org:~/x me@example.org: hi me@example.org:",
+        "|me@example.org:~/x| |me@example.org|: hi |me@example.org|:",
+    );
+
+    let links: Vec<_> = finder
+        .links("git@github.com:owner/repo.git a@example.org")
+        .collect();
+    assert_eq!(links[0].kind(), &LinkKind::Url);
+    assert_eq!(
+        links[0].scp_parts(),
+        Some(("git", "github.com", "owner/repo.git"))
+    );
+    assert_eq!(links[0].host(), Some("github.com"));
+    assert_eq!(links[0].scheme(), None);
+    assert_eq!(links[1].kind(), &LinkKind::Email);
+    assert_eq!(links[1].scp_parts(), None);
+
+    // Other URLs with `@` and `:` are not SCP-like URLs
+    let link = finder.links("http://a@example.org:80/").next().unwrap();
+    assert_eq!(link.scp_parts(), None);
+
+    // Only with URLs enabled
+    finder.kinds(&[LinkKind::Email]);
+    assert_linked_with(&finder, "git@a.org:repo", "|git@a.org|:repo");
+}
+
+#[test]
+fn allow_spaces_when_delimited() {
+    let mut finder = LinkFinder::new();
+    finder.url_allow_spaces_when_delimited(true);
+    assert_linked_with(
+        &finder,
+        "see <http://example.com/a b> now",
+        "see <|http://example.com/a b|> now",
+    );
+    assert_linked_with(
+        &finder,
+        "\"http://example.com/My Documents/a  b.pdf\"",
+        "\"|http://example.com/My Documents/a  b.pdf|\"",
+    );
+    assert_linked_with(
+        &finder,
+        "<http://a.com/x y> and <http://b.com/>",
+        "<|http://a.com/x y|> and <|http://b.com/|>",
+    );
+    // Punctuation before a space is part of it, up to the closing delimiter
+    assert_linked_with(&finder, "<http://a.org/a. b>", "<|http://a.org/a. b|>");
+    assert_linked_with(
+        &finder,
+        "\"http://a.org/a, b.\"",
+        "\"|http://a.org/a, b.|\"",
+    );
+
+    // Only an opening delimiter, or the closing one is on another line
+    assert_linked_with(
+        &finder,
+        "<http://example.com/a b",
+        "<|http://example.com/a| b",
+    );
+    assert_linked_with(
+        &finder,
+        "\"http://example.com/a b\nc\"",
+        "\"|http://example.com/a| b\nc\"",
+    );
+    // Not enclosed, or other characters that can't be in URLs before the closing delimiter
+    assert_linked_with(
+        &finder,
+        "(http://example.com/a b)",
+        "(|http://example.com/a| b)",
+    );
+    assert_linked_with(
+        &finder,
+        "<http://example.com/a <b>>",
+        "<|http://example.com/a| <b>>",
+    );
+    assert_linked_with(
+        &finder,
+        "<http://example.com/a >",
+        "<|http://example.com/a| >",
+    );
+
+    // Off by default
+    assert_linked("<http://example.com/a b>", "<|http://example.com/a| b>");
+}
+
+#[test]
+fn require_path() {
+    let mut finder = LinkFinder::new();
+    finder.url_require_path(true);
+    assert_linked_with(&finder, "http://example.org", "http://example.org");
+    assert_linked_with(&finder, "http://example.org.", "http://example.org.");
+    assert_linked_with(&finder, "http://example.org?", "http://example.org?");
+    assert_linked_with(&finder, "http://example.org/", "|http://example.org/|");
+    assert_linked_with(&finder, "http://example.org?a", "|http://example.org?a|");
+    assert_linked_with(&finder, "http://example.org#a", "|http://example.org#a|");
+    assert_linked_with(
+        &finder,
+        "http://example.org:8080/",
+        "|http://example.org:8080/|",
+    );
+    assert_linked_with(&finder, "file:///tmp", "|file:///tmp|");
+
+    finder.url_must_have_scheme(false);
+    finder.url_allow_scheme_relative(true);
+    assert_linked_with(&finder, "example.org", "example.org");
+    assert_linked_with(&finder, "//example.org", "//example.org");
+    assert_linked_with(&finder, "example.org/a", "|example.org/a|");
+    assert_linked_with(&finder, "//example.org/a", "|//example.org/a|");
+    assert_linked_with(&finder, "example.org#a", "|example.org#a|");
+
+    // Emails are not affected
+    assert_linked_with(&finder, "a@example.org", "|a@example.org|");
+}
+
+#[test]
+fn split_on_bidi_controls() {
+    assert_linked(
+        "\u{200F}https://example.com\u{200F}",
+        "\u{200F}|https://example.com\u{200F}|",
+    );
+
+    let mut finder = LinkFinder::new();
+    finder.url_split_on_bidi_controls(true);
+    assert_linked_with(
+        &finder,
+        "\u{200F}https://example.com\u{200F}",
+        "\u{200F}|https://example.com|\u{200F}",
+    );
+    assert_linked_with(
+        &finder,
+        "\u{5D0} \u{2067}https://example.com/\u{5D1}\u{2069} \u{5D2}",
+        "\u{5D0} \u{2067}|https://example.com/\u{5D1}|\u{2069} \u{5D2}",
+    );
+    assert_linked_with(
+        &finder,
+        "\u{200E}https://example.com/a\u{200E}b",
+        "\u{200E}|https://example.com/a|\u{200E}b",
+    );
+    assert_linked_with(
+        &finder,
+        "\u{200F}a@example.com\u{200F}",
+        "\u{200F}|a@example.com|\u{200F}",
+    );
+
+    finder.url_must_have_scheme(false);
+    assert_linked_with(
+        &finder,
+        "\u{200F}example.com\u{200E}.",
+        "\u{200F}|example.com|\u{200E}.",
+    );
+}
+
+#[test]
+fn literal_percent() {
+    assert_linked(
+        "https://example.org/sale-50%-off",
+        "|https://example.org/sale-50%-off|",
+    );
+    assert_linked("50% off https://x.org/", "50% off |https://x.org/|");
+
+    let mut finder = LinkFinder::new();
+    finder.url_literal_percent(false);
+    assert_linked_with(
+        &finder,
+        "https://example.org/sale-50%-off",
+        "|https://example.org/sale-50|%-off",
+    );
+    assert_linked_with(
+        &finder,
+        "https://example.org/a%20b?q=%C3%A9#%7e",
+        "|https://example.org/a%20b?q=%C3%A9#%7e|",
+    );
+    assert_linked_with(
+        &finder,
+        "https://example.org/?q=100% https://example.org/a%2",
+        "|https://example.org/?q=100|% |https://example.org/a|%2",
+    );
+    assert_linked_with(
+        &finder,
+        "50% off https://x.org/",
+        "50% off |https://x.org/|",
+    );
+}
+
+#[test]
+fn max_scheme_len() {
+    let scheme = "a".repeat(64);
+    let url = format!("{}://example.org", scheme);
+    assert_linked(&url, &format!("|{}|", url));
+    let url = format!("a{}://example.org", scheme);
+    assert_linked(&url, &url);
+
+    let mut finder = LinkFinder::new();
+    finder.url_max_scheme_len(5);
+    assert_linked_with(
+        &finder,
+        "https://a.org foo+ab://b.org",
+        "|https://a.org| foo+ab://b.org",
+    );
+
+    // Long pseudo-schemes are rejected without scanning all of them
+    let input = format!("{}://example.org", "a+".repeat(50_000));
+    assert_linked(&input, &input);
+
+    // The same limit applies to `is_url`
+    let options = UrlOptions::new();
+    assert!(is_url(&format!("{}://example.org", scheme), &options));
+    assert!(!is_url(&format!("a{}://example.org", scheme), &options));
+}
+
+#[test]
+fn schemeless_port() {
+    assert_urls_without_protocol("example.com:8080/path", "|example.com:8080/path|");
+    assert_urls_without_protocol("example.com:8080", "|example.com:8080|");
+    assert_urls_without_protocol("(example.com:8080).", "(|example.com:8080|).");
+    assert_urls_without_protocol("example.com:8080?q=1", "|example.com:8080?q=1|");
+    assert_urls_without_protocol("example.com:", "|example.com|:");
+    assert_urls_without_protocol(
+        "Visit example.com: it's great",
+        "Visit |example.com|: it's great",
+    );
+    // Only digits are a port
+    assert_urls_without_protocol("example.com:abc", "|example.com|:abc");
+    assert_urls_without_protocol("example.com:80a/path", "|example.com|:80a/path");
+}
+
+#[test]
+fn require_dotted_host() {
+    let mut finder = LinkFinder::new();
+    finder.url_require_dotted_host(true);
+    assert_linked_with(&finder, "http://a.bc/", "|http://a.bc/|");
+    assert_linked_with(&finder, "http://a/", "http://a/");
+    assert_linked_with(
+        &finder,
+        "see http://localhost:8080/x",
+        "see http://localhost:8080/x",
+    );
+    assert_linked_with(&finder, "https://intranet./", "https://intranet./");
+    assert_linked_with(&finder, "https://user@intranet/", "https://user@intranet/");
+    assert_linked_with(
+        &finder,
+        "http://127.0.0.1:8080/",
+        "|http://127.0.0.1:8080/|",
+    );
+    assert_linked_with(&finder, "http://[::1]/", "|http://[::1]/|");
+    assert_linked_with(
+        &finder,
+        "https://user@a.example/",
+        "|https://user@a.example/|",
+    );
+
+    // Not for schemes that don't require a host, or for known hosts
+    assert_linked_with(&finder, "custom://intranet/", "|custom://intranet/|");
+    assert_linked_with(&finder, "file:///tmp", "|file:///tmp|");
+    finder.url_known_hosts(&["localhost"]);
+    assert_linked_with(&finder, "http://localhost/", "|http://localhost/|");
+
+    // Off by default
+    assert_linked("http://a/", "|http://a/|");
+}
+
+#[test]
+fn min_host_length() {
+    assert_urls_without_protocol("e.com", "|e.com|");
+
+    let mut finder = LinkFinder::new();
+    finder.url_must_have_scheme(false);
+    finder.url_min_host_length(6);
+    assert_linked_with(&finder, "e.com", "e.com");
+    assert_linked_with(&finder, "e.com/path e.com:8080", "e.com/path e.com:8080");
+    assert_linked_with(&finder, "ab.com", "|ab.com|");
+    assert_linked_with(&finder, "example.com/a", "|example.com/a|");
+    // Counted in characters, not bytes
+    assert_linked_with(&finder, "\u{E4}.com", "\u{E4}.com");
+    assert_linked_with(&finder, "\u{E4}\u{F6}.com", "|\u{E4}\u{F6}.com|");
+    // Scheme-relative URLs count the host without the port
+    finder.url_allow_scheme_relative(true);
+    assert_linked_with(&finder, "//e.com //e.com:8080/x", "//e.com //e.com:8080/x");
+    assert_linked_with(&finder, "//ab.com/x", "|//ab.com/x|");
+    assert_linked_with(&finder, "//[::1]/x", "//[::1]/x");
+    assert_linked_with(&finder, "//[::1:2]:80/x", "|//[::1:2]:80/x|");
+    // URLs with a scheme and emails are not affected
+    assert_linked_with(
+        &finder,
+        "https://e.com a@e.com",
+        "|https://e.com| |a@e.com|",
+    );
+}
+
+#[test]
+fn allow_short_ipv4() {
+    assert_linked("http://10.0.1/", "http://10.0.1/");
+    assert_linked("http://127.1/", "http://127.1/");
+
+    let mut finder = LinkFinder::new();
+    finder.url_allow_short_ipv4(true);
+    assert_linked_with(&finder, "http://10.0.1/", "|http://10.0.1/|");
+    assert_linked_with(&finder, "http://127.1:8080/", "|http://127.1:8080/|");
+    assert_linked_with(&finder, "http://192.168.0.1/", "|http://192.168.0.1/|");
+    assert_linked_with(&finder, "http://10/", "http://10/");
+    assert_linked_with(&finder, "http://1.2.3.4.5/", "http://1.2.3.4.5/");
+
+    // URLs without a scheme and emails still need 4 parts
+    finder.url_must_have_scheme(false);
+    assert_linked_with(&finder, "10.0.1/a", "10.0.1/a");
+    assert_linked_with(&finder, "a@10.0.1", "a@10.0.1");
+}
+
+#[test]
+fn scheme_keep_trailing() {
+    assert_linked("slack://x!", "|slack://x|!");
+
+    let mut finder = LinkFinder::new();
+    finder.scheme_keep_trailing(&[("slack", &['!']), ("app", &['!', '.'])]);
+    assert_linked_with(&finder, "slack://x!", "|slack://x!|");
+    assert_linked_with(&finder, "SLACK://x!!", "|SLACK://x!!|");
+    assert_linked_with(&finder, "slack://x/channel!", "|slack://x/channel!|");
+    assert_linked_with(&finder, "(slack://x!)", "(|slack://x!|)");
+    assert_linked_with(&finder, "slack://x.", "|slack://x|.");
+    assert_linked_with(&finder, "app://x/y!.", "|app://x/y!.|");
+    assert_linked_with(&finder, "http://x!", "|http://x|!");
+    assert_linked_with(&finder, "http://x.org/a!", "|http://x.org/a|!");
+}
+
 /// Assert link with protocol
 fn assert_linked(input: &str, expected: &str) {
     let finder = LinkFinder::new();